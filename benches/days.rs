@@ -0,0 +1,194 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    hint::black_box,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use advent_2022::runner::{self, Solution};
+use eyre::{bail, Context, ContextCompat};
+
+const TARGET_TIME: Duration = Duration::from_secs(2);
+const MIN_SAMPLES: usize = 5;
+const MAX_SAMPLES: usize = 500;
+
+#[derive(Default)]
+struct Options {
+    filter: Option<String>,
+    save_baseline: Option<String>,
+    baseline: Option<String>,
+}
+
+fn parse_options() -> eyre::Result<Options> {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save-baseline" => options.save_baseline = args.next(),
+            "--baseline" => options.baseline = args.next(),
+            "--bench" => {}
+            flag if flag.starts_with("--") => bail!("unknown flag {}", flag),
+            filter => options.filter = Some(filter.to_string()),
+        }
+    }
+
+    Ok(options)
+}
+
+struct Summary {
+    samples: usize,
+    median: Duration,
+    p10: Duration,
+    p90: Duration,
+    p99: Duration,
+}
+
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    let index = (sorted.len() - 1) * p / 100;
+    sorted[index]
+}
+
+fn measure<F: FnMut()>(mut f: F) -> Summary {
+    let start = Instant::now();
+    let mut samples = vec![];
+    while samples.len() < MIN_SAMPLES
+        || (samples.len() < MAX_SAMPLES && start.elapsed() < TARGET_TIME)
+    {
+        let sample = Instant::now();
+        f();
+        samples.push(sample.elapsed());
+    }
+
+    samples.sort_unstable();
+    Summary {
+        samples: samples.len(),
+        median: percentile(&samples, 50),
+        p10: percentile(&samples, 10),
+        p90: percentile(&samples, 90),
+        p99: percentile(&samples, 99),
+    }
+}
+
+fn baseline_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("target")
+        .join("bench-baselines")
+        .join(format!("{}.txt", name))
+}
+
+fn load_baseline(name: &str) -> eyre::Result<BTreeMap<String, Duration>> {
+    let path = baseline_path(name);
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("reading baseline {}", path.display()))?;
+    contents
+        .lines()
+        .map(|line| {
+            let (id, nanos) = line
+                .split_once(' ')
+                .with_context(|| format!("malformed baseline line {:?}", line))?;
+            let nanos = nanos.parse::<u64>()?;
+            Ok((id.to_string(), Duration::from_nanos(nanos)))
+        })
+        .collect()
+}
+
+/// Merges `medians` into the named baseline, keeping the entries of any
+/// benchmarks a filter left out of this run.
+fn save_baseline(name: &str, medians: &BTreeMap<String, Duration>) -> eyre::Result<()> {
+    let path = baseline_path(name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut merged = if path.exists() {
+        load_baseline(name)?
+    } else {
+        BTreeMap::new()
+    };
+    merged.extend(medians.iter().map(|(id, median)| (id.clone(), *median)));
+    let contents = merged
+        .iter()
+        .map(|(id, median)| format!("{} {}\n", id, median.as_nanos()))
+        .collect::<String>();
+    fs::write(&path, contents).with_context(|| format!("writing baseline {}", path.display()))
+}
+
+fn report(id: &str, summary: &Summary, baseline: Option<Duration>) {
+    let change = baseline
+        .map(|before| {
+            let before = before.as_secs_f64();
+            let after = summary.median.as_secs_f64();
+            format!("  {:+.1}%", (after - before) / before * 100.0)
+        })
+        .unwrap_or_default();
+
+    println!(
+        "{:<16} median {:>10.2?}  p10 {:>10.2?}  p90 {:>10.2?}  p99 {:>10.2?}  n={:<3}{}",
+        id, summary.median, summary.p10, summary.p90, summary.p99, summary.samples, change
+    );
+}
+
+fn bench_day(
+    solution: &Solution,
+    options: &Options,
+    baseline: &BTreeMap<String, Duration>,
+    medians: &mut BTreeMap<String, Duration>,
+) -> eyre::Result<()> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("input")
+        .join("2022")
+        .join(format!("day{}.txt", solution.day));
+    let Ok(input) = fs::read_to_string(&path) else {
+        println!(
+            "day{:<13} skipped, no input at {}",
+            solution.day,
+            path.display()
+        );
+        return Ok(());
+    };
+
+    let mut run = |id: String, f: &mut dyn FnMut()| {
+        if options
+            .filter
+            .as_ref()
+            .is_none_or(|filter| id.contains(filter))
+        {
+            let summary = measure(f);
+            report(&id, &summary, baseline.get(&id).copied());
+            medians.insert(id, summary.median);
+        }
+    };
+
+    run(format!("day{}/generator", solution.day), &mut || {
+        black_box(solution.generate(black_box(&input)).ok());
+    });
+
+    let parsed = solution.generate(&input)?;
+    for part in [1, 2] {
+        run(format!("day{}/part{}", solution.day, part), &mut || {
+            black_box(solution.solve(part, black_box(parsed.as_ref())).ok());
+        });
+    }
+
+    Ok(())
+}
+
+fn main() -> eyre::Result<()> {
+    let options = parse_options()?;
+    let baseline = match &options.baseline {
+        Some(name) => load_baseline(name)?,
+        None => BTreeMap::new(),
+    };
+
+    let mut medians = BTreeMap::new();
+    for solution in runner::solutions() {
+        bench_day(&solution, &options, &baseline, &mut medians)?;
+    }
+
+    if let Some(name) = &options.save_baseline {
+        save_baseline(name, &medians)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,348 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use advent_2022::{
+    ledger::{Ledger, Verdict},
+    runner::{self, Answer, Solution},
+};
+use eyre::{bail, eyre, Context, ContextCompat};
+
+const USAGE: &str = "\
+Usage: solve <DAY|all> [PART|all] [--input PATH] [--json] [--verify | --record]
+
+Runs the solutions for DAY (1-16) or every day. The puzzle input is read from
+PATH, or from stdin if PATH is `-`; it defaults to input/2022/dayN.txt.
+
+--verify compares every answer against the ledger in input/2022/answers.txt
+and fails on any mismatch or missing entry. --record writes the answers into
+the ledger. The ledger holds answers for the default inputs, so neither can be
+used with --input.";
+
+const LEDGER_PATH: &str = "input/2022/answers.txt";
+
+#[derive(Default)]
+struct Options {
+    days: Vec<u8>,
+    parts: Vec<u8>,
+    input: Option<String>,
+    json: bool,
+    verify: bool,
+    record: bool,
+}
+
+fn parse_options() -> eyre::Result<Options> {
+    let mut options = Options::default();
+    let mut positional = vec![];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => options.json = true,
+            "--verify" => options.verify = true,
+            "--record" => options.record = true,
+            "--input" => options.input = Some(args.next().context("--input needs a path")?),
+            "-h" | "--help" => bail!(USAGE),
+            flag if flag.starts_with("--") => bail!("unknown flag {}\n\n{}", flag, USAGE),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    options.days = match positional.next().as_deref() {
+        Some("all") => runner::solutions().iter().map(|s| s.day).collect(),
+        Some(day) => vec![day
+            .parse()
+            .with_context(|| format!("invalid day {:?}", day))?],
+        None => bail!(USAGE),
+    };
+    options.parts = match positional.next().as_deref() {
+        None | Some("all") => vec![1, 2],
+        Some(part @ ("1" | "2")) => vec![part.parse()?],
+        Some(part) => bail!("invalid part {:?}, expected 1, 2 or all", part),
+    };
+
+    if let Some(extra) = positional.next() {
+        bail!("unexpected argument {:?}\n\n{}", extra, USAGE);
+    }
+
+    if options.input.is_some() && options.days.len() > 1 {
+        bail!("--input can only be used when solving a single day");
+    }
+
+    if options.verify && options.record {
+        bail!("--verify and --record cannot be used together");
+    }
+
+    if options.input.is_some() && (options.verify || options.record) {
+        bail!("--verify and --record check the default inputs, so cannot be used with --input");
+    }
+
+    Ok(options)
+}
+
+fn read_input(day: u8, path: Option<&str>) -> eyre::Result<String> {
+    match path {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+        Some(path) => fs::read_to_string(path).with_context(|| format!("reading {}", path)),
+        None => {
+            let path = format!("input/2022/day{}.txt", day);
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path))
+        }
+    }
+}
+
+struct PartResult {
+    part: u8,
+    answer: eyre::Result<Answer>,
+    time: Duration,
+}
+
+struct DayResult {
+    day: u8,
+    parse_time: Duration,
+    parts: eyre::Result<Vec<PartResult>>,
+}
+
+fn run_day(solution: &Solution, input: &str, parts: &[u8]) -> DayResult {
+    let start = Instant::now();
+    let parsed = solution.generate(input);
+    let parse_time = start.elapsed();
+
+    let parts = parsed.map_err(|e| eyre!(e)).map(|parsed| {
+        parts
+            .iter()
+            .map(|&part| {
+                let start = Instant::now();
+                let answer = solution.solve(part, parsed.as_ref());
+                PartResult {
+                    part,
+                    answer,
+                    time: start.elapsed(),
+                }
+            })
+            .collect()
+    });
+
+    DayResult {
+        day: solution.day,
+        parse_time,
+        parts,
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+fn load_ledger() -> eyre::Result<Ledger> {
+    fs::read_to_string(LEDGER_PATH)
+        .with_context(|| format!("reading {}", LEDGER_PATH))?
+        .parse()
+}
+
+fn json_value(answer: &Answer) -> String {
+    match answer {
+        Answer::Number(n) => n.to_string(),
+        Answer::Text(s) => json_string(s),
+    }
+}
+
+fn json_answer(answer: &eyre::Result<Answer>) -> String {
+    match answer {
+        Ok(answer) => format!("\"answer\":{}", json_value(answer)),
+        Err(e) => format!("\"error\":{}", json_string(&format!("{:#}", e))),
+    }
+}
+
+fn json_expected(ledger: Option<&Ledger>, day: u8, part: u8) -> String {
+    match ledger.and_then(|ledger| ledger.get(day, part)) {
+        Some(expected) => format!(",\"expected\":{}", json_value(expected)),
+        None => String::new(),
+    }
+}
+
+fn print_json(results: &[DayResult], ledger: Option<&Ledger>) {
+    let days = results
+        .iter()
+        .map(|result| {
+            let parts = match &result.parts {
+                Ok(parts) => {
+                    let parts = parts
+                        .iter()
+                        .map(|p| {
+                            format!(
+                                "{{\"part\":{},{}{},\"solve_ns\":{}}}",
+                                p.part,
+                                json_answer(&p.answer),
+                                json_expected(ledger, result.day, p.part),
+                                p.time.as_nanos()
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    format!("\"parts\":[{}]", parts.join(","))
+                }
+                Err(e) => format!("\"error\":{}", json_string(&e.to_string())),
+            };
+
+            format!(
+                "{{\"day\":{},\"parse_ns\":{},{}}}",
+                result.day,
+                result.parse_time.as_nanos(),
+                parts
+            )
+        })
+        .collect::<Vec<_>>();
+
+    println!("[{}]", days.join(","));
+}
+
+fn print_text(results: &[DayResult], ledger: Option<&Ledger>) {
+    for result in results {
+        match &result.parts {
+            Ok(parts) => {
+                for part in parts {
+                    match &part.answer {
+                        Ok(answer) => {
+                            let verdict = match ledger
+                                .map(|ledger| ledger.check(result.day, part.part, answer))
+                            {
+                                None => String::new(),
+                                Some(Verdict::Correct) => " (ok)".to_string(),
+                                Some(Verdict::Incorrect { expected }) => {
+                                    format!(" (MISMATCH, expected {})", expected)
+                                }
+                                Some(Verdict::Unrecorded) => " (unrecorded)".to_string(),
+                            };
+                            println!(
+                                "day {} part {}: {}{}",
+                                result.day, part.part, answer, verdict
+                            )
+                        }
+                        Err(e) => {
+                            eprintln!("day {} part {}: error: {:#}", result.day, part.part, e)
+                        }
+                    }
+                }
+            }
+            Err(e) => eprintln!("day {}: {}", result.day, e),
+        }
+    }
+}
+
+fn record(results: &[DayResult]) -> eyre::Result<()> {
+    let mut ledger = match fs::read_to_string(LEDGER_PATH) {
+        Ok(contents) => contents.parse()?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ledger::default(),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", LEDGER_PATH)),
+    };
+
+    for result in results {
+        for part in result.parts.iter().flatten() {
+            if let Ok(answer) = &part.answer {
+                ledger.insert(result.day, part.part, answer.clone());
+            }
+        }
+    }
+
+    fs::write(LEDGER_PATH, ledger.to_string()).with_context(|| format!("writing {}", LEDGER_PATH))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut results = vec![];
+    for &day in &options.days {
+        let result = runner::solution(day)
+            .ok_or_else(|| eyre!("day {} is not solved", day))
+            .and_then(|solution| {
+                let input = read_input(day, options.input.as_deref())?;
+                Ok(run_day(&solution, &input, &options.parts))
+            });
+
+        match result {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                eprintln!("{:#}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let ledger = match (options.verify, load_ledger()) {
+        (false, _) => None,
+        (true, Ok(ledger)) => Some(ledger),
+        (true, Err(e)) => {
+            eprintln!("{:#}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if options.json {
+        print_json(&results, ledger.as_ref());
+    } else {
+        print_text(&results, ledger.as_ref());
+    }
+
+    let failed = results.iter().any(|result| match &result.parts {
+        Ok(parts) => parts.iter().any(|part| match (&part.answer, &ledger) {
+            (Err(_), _) => true,
+            (Ok(answer), Some(ledger)) => {
+                ledger.check(result.day, part.part, answer) != Verdict::Correct
+            }
+            (Ok(_), None) => false,
+        }),
+        Err(_) => true,
+    });
+
+    if options.record {
+        if let Err(e) = record(&results) {
+            eprintln!("{:#}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("CMZ"), "\"CMZ\"");
+        assert_eq!(json_string("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+}
@@ -1,36 +1,365 @@
+//! Day 1: Calorie Counting.
+
+use std::{cmp::Reverse, collections::BinaryHeap, io::BufRead};
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::ContextCompat;
+use nom::{character::complete::u64, combinator::map};
 
-#[aoc_generator(day1)]
-fn generator(input: &str) -> eyre::Result<Vec<Vec<usize>>> {
-    input.lines().try_fold(vec![vec![]], |mut acc, line| {
-        if line.is_empty() {
-            acc.push(vec![]);
-            return Ok(acc);
+use crate::error::{parse_complete, read_lines, ParseError, ReadError};
+
+/// How [`parse`] treats irregular layout.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Mode {
+    /// Accepts `\r\n` line endings and whitespace-only lines as blank, merges
+    /// runs of blank lines and ignores blank lines at either end.
+    #[default]
+    Lenient,
+    /// Rejects each of those, pointing at the offending line.
+    Strict,
+}
+
+/// The elves read so far, and whether a blank line is waiting to start a new one.
+#[derive(Default)]
+struct Elves {
+    elves: Vec<Vec<usize>>,
+    separated: bool,
+    /// The calories carried by every elf together, which bounds every sum
+    /// taken over them.
+    total: usize,
+}
+
+impl Elves {
+    fn add_line(&mut self, mode: Mode, input: &str, line: &str) -> Result<(), ParseError> {
+        let error = |position: &str, expected: &str| ParseError::at(1, input, position, expected);
+        let content = match mode {
+            Mode::Lenient => line.trim(),
+            Mode::Strict => {
+                if let Some(i) = line.find('\r') {
+                    return Err(error(&line[i..], "a `\\n` line ending"));
+                }
+                if line.trim().is_empty() && !line.is_empty() {
+                    return Err(error(line, "a calorie count or an empty line"));
+                }
+                line
+            }
+        };
+
+        if content.is_empty() {
+            if mode == Mode::Strict && (self.separated || self.elves.is_empty()) {
+                return Err(error(line, "a calorie count"));
+            }
+
+            self.separated = true;
+            return Ok(());
+        }
+
+        let value = parse_complete(
+            1,
+            input,
+            content,
+            map(u64, |n| n as usize),
+            "a calorie count",
+        )?;
+        self.total = self
+            .total
+            .checked_add(value)
+            .ok_or_else(|| error(content, "a calorie count that keeps the total in range"))?;
+        if self.separated || self.elves.is_empty() {
+            self.elves.push(vec![]);
+            self.separated = false;
+        }
+        if let Some(current) = self.elves.last_mut() {
+            current.push(value);
+        }
+
+        Ok(())
+    }
+
+    fn finish(self, mode: Mode, input: &str) -> Result<Vec<Vec<usize>>, ParseError> {
+        if mode == Mode::Strict && self.separated {
+            return Err(ParseError::new(1, input, input.len(), "a calorie count"));
         }
 
-        let current = acc.last_mut().context("unable to get last element")?;
-        let value = line.parse()?;
-        current.push(value);
+        Ok(self.elves)
+    }
+}
+
+/// Parses each elf's calorie counts, with elves separated by a blank line.
+/// The counts must add up to no more than `usize::MAX`, so no total over
+/// them can overflow. A final `\n` ends the last line rather than starting
+/// a blank one.
+pub fn parse(input: &str, mode: Mode) -> Result<Vec<Vec<usize>>, ParseError> {
+    let input = input.strip_suffix('\n').unwrap_or(input);
+    let mut elves = Elves::default();
+    for line in input.split('\n') {
+        elves.add_line(mode, input, line)?;
+    }
+
+    elves.finish(mode, input)
+}
+
+/// Parses each elf's calorie counts leniently; see [`Mode::Lenient`].
+#[aoc_generator(day1)]
+pub fn generator(input: &str) -> Result<Vec<Vec<usize>>, ParseError> {
+    parse(input, Mode::Lenient)
+}
+
+/// Streaming version of [`generator`].
+pub fn read(reader: impl BufRead) -> Result<Vec<Vec<usize>>, ReadError> {
+    let mut elves = Elves::default();
+    read_lines(reader, |line| elves.add_line(Mode::Lenient, line, line))?;
+    Ok(elves.finish(Mode::Lenient, "")?)
+}
+
+/// One elf's load. Elves are ranked by total, highest first; elves with the
+/// same total share a rank, as in 1, 2, 2, 4.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Elf {
+    /// The elf's position in the input.
+    pub index: usize,
+    /// The elf's competition rank, starting at 1.
+    pub rank: usize,
+    /// The calories the elf carries in total.
+    pub total: usize,
+    /// The number of food items the elf carries.
+    pub items: usize,
+}
+
+/// Ordering key for ranking: highest total first, then input order.
+fn rank_key(index: usize, total: usize) -> (usize, Reverse<usize>) {
+    (total, Reverse(index))
+}
+
+/// Fills in competition ranks for elves already in ranked order.
+fn assign_ranks(elves: &mut [Elf]) {
+    for i in 0..elves.len() {
+        elves[i].rank = match i {
+            0 => 1,
+            _ if elves[i].total == elves[i - 1].total => elves[i - 1].rank,
+            _ => i + 1,
+        };
+    }
+}
 
-        Ok(acc)
+fn elves(input: &[Vec<usize>]) -> impl Iterator<Item = Elf> + '_ {
+    input.iter().enumerate().map(|(index, values)| Elf {
+        index,
+        rank: 0,
+        total: values.iter().sum(),
+        items: values.len(),
     })
 }
 
+/// The `k` elves carrying the most calories, in ranked order. Only `k`
+/// elves are held at a time, so this is cheaper than sorting every elf.
+pub fn top_n(input: &[Vec<usize>], k: usize) -> Vec<Elf> {
+    let mut heap = BinaryHeap::with_capacity(k.min(input.len()) + 1);
+    for elf in elves(input) {
+        heap.push(Reverse((rank_key(elf.index, elf.total), elf.items)));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    let mut top = heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(((total, Reverse(index)), items))| Elf {
+            index,
+            rank: 0,
+            total,
+            items,
+        })
+        .collect::<Vec<_>>();
+    assign_ranks(&mut top);
+    top
+}
+
+/// Summary statistics over every elf's total.
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    ranked: Vec<Elf>,
+}
+
+impl Statistics {
+    /// Ranks every elf in `input`.
+    pub fn new(input: &[Vec<usize>]) -> Self {
+        let mut ranked = elves(input).collect::<Vec<_>>();
+        ranked.sort_unstable_by_key(|elf| Reverse(rank_key(elf.index, elf.total)));
+        assign_ranks(&mut ranked);
+        Self { ranked }
+    }
+
+    /// Every elf in ranked order.
+    pub fn ranked(&self) -> &[Elf] {
+        &self.ranked
+    }
+
+    /// The mean total, or `None` if there are no elves.
+    pub fn mean(&self) -> Option<f64> {
+        let total = self.ranked.iter().map(|elf| elf.total).sum::<usize>();
+        (!self.ranked.is_empty()).then(|| total as f64 / self.ranked.len() as f64)
+    }
+
+    /// The middle total, or the mean of the two middle totals.
+    pub fn median(&self) -> Option<f64> {
+        let n = self.ranked.len();
+        match n {
+            0 => None,
+            _ if n % 2 == 1 => Some(self.ranked[n / 2].total as f64),
+            _ => Some((self.ranked[n / 2 - 1].total + self.ranked[n / 2].total) as f64 / 2.0),
+        }
+    }
+
+    /// The nearest-rank `p`th percentile of the totals, for `p` in 0-100.
+    pub fn percentile(&self, p: f64) -> Option<usize> {
+        if self.ranked.is_empty() || !(0.0..=100.0).contains(&p) {
+            return None;
+        }
+
+        let n = self.ranked.len();
+        let rank = ((p / 100.0 * n as f64).ceil() as usize).max(1);
+        Some(self.ranked[n - rank].total)
+    }
+
+    /// The indices of elves who share a total with another elf, grouped by
+    /// total in ranked order.
+    pub fn ties(&self) -> Vec<Vec<usize>> {
+        self.ranked
+            .chunk_by(|a, b| a.total == b.total)
+            .filter(|group| group.len() > 1)
+            .map(|group| group.iter().map(|elf| elf.index).collect())
+            .collect()
+    }
+}
+
+/// The most calories carried by a single elf.
 #[aoc(day1, part1)]
-fn part1(input: &[Vec<usize>]) -> Option<usize> {
-    input
-        .iter()
-        .map(|values| values.iter().sum::<usize>())
-        .max()
+pub fn part1(input: &[Vec<usize>]) -> Option<usize> {
+    top_n(input, 1).first().map(|elf| elf.total)
 }
 
+/// The total calories carried by the top three elves.
 #[aoc(day1, part2)]
-fn part2(input: &[Vec<usize>]) -> usize {
-    let mut totals = input
-        .iter()
-        .map(|values| values.iter().sum::<usize>())
-        .collect::<Vec<usize>>();
-    totals.sort_unstable();
-    totals.iter().rev().take(3).sum()
+pub fn part2(input: &[Vec<usize>]) -> usize {
+    top_n(input, 3).iter().map(|elf| elf.total).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day1")).unwrap();
+        assert_eq!(input.len(), 5);
+        assert_eq!(input[0], vec![1000, 2000, 3000]);
+        assert_eq!(input[4], vec![10000]);
+    }
+
+    #[test]
+    fn test_generator_error() {
+        let error = generator("1000\n20x0").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+
+        let max = u64::MAX.to_string();
+        let error = generator(&format!("{}\n\n{}", max, max)).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+        assert!(generator(&format!("{}\n\n0", max)).is_ok());
+    }
+
+    #[test]
+    fn test_modes() {
+        let input = "\n1000\r\n2000\n \t\n\n3000\n\n";
+        assert_eq!(
+            parse(input, Mode::Lenient).unwrap(),
+            [vec![1000, 2000], vec![3000]]
+        );
+        assert_eq!(parse("", Mode::Lenient).unwrap(), Vec::<Vec<usize>>::new());
+
+        let strict = |input| parse(input, Mode::Strict).map_err(|e| (e.line, e.column));
+        assert_eq!(strict("1\n\n2").unwrap(), [vec![1], vec![2]]);
+        assert_eq!(strict("1\r\n2"), Err((1, 2)));
+        assert_eq!(strict("1\n  \n2"), Err((2, 1)));
+        assert_eq!(strict("1\n\n\n2"), Err((3, 1)));
+        assert_eq!(strict("\n1"), Err((1, 1)));
+        assert_eq!(strict("1\n").unwrap(), [vec![1]]);
+        assert_eq!(strict("1\n\n"), Err((2, 1)));
+        assert_eq!(strict("1\n\n2\n").unwrap(), [vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn test_read() {
+        let input = example!("day1");
+        assert_eq!(read(input.as_bytes()).unwrap(), generator(input).unwrap());
+
+        let error = read("1000\n\n20x0\n".as_bytes()).unwrap_err();
+        assert!(matches!(
+            error,
+            ReadError::Parse(ParseError {
+                line: 3,
+                column: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let input = generator(example!("day1")).unwrap();
+        let statistics = Statistics::new(&input);
+        let order = statistics
+            .ranked()
+            .iter()
+            .map(|elf| elf.index)
+            .collect::<Vec<_>>();
+        assert_eq!(order, [3, 2, 4, 0, 1]);
+        assert_eq!(
+            statistics.ranked()[1],
+            Elf {
+                index: 2,
+                rank: 2,
+                total: 11000,
+                items: 2
+            }
+        );
+        assert_eq!(statistics.mean(), Some(11000.0));
+        assert_eq!(statistics.median(), Some(10000.0));
+        assert_eq!(statistics.percentile(0.0), Some(4000));
+        assert_eq!(statistics.percentile(50.0), Some(10000));
+        assert_eq!(statistics.percentile(90.0), Some(24000));
+        assert_eq!(statistics.percentile(101.0), None);
+        assert!(statistics.ties().is_empty());
+        assert_eq!(top_n(&input, 2), &statistics.ranked()[..2]);
+        assert_eq!(top_n(&input, usize::MAX), statistics.ranked());
+        assert_eq!(top_n(&input, 1 << 40), statistics.ranked());
+    }
+
+    #[test]
+    fn test_ties() {
+        let input = vec![vec![1, 1], vec![3], vec![2], vec![2], vec![1]];
+        let statistics = Statistics::new(&input);
+        let ranks = statistics
+            .ranked()
+            .iter()
+            .map(|elf| (elf.index, elf.rank))
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, [(1, 1), (0, 2), (2, 2), (3, 2), (4, 5)]);
+        assert_eq!(statistics.ties(), [vec![0, 2, 3]]);
+        assert_eq!(statistics.median(), Some(2.0));
+        assert_eq!(top_n(&input, 2), &statistics.ranked()[..2]);
+        assert_eq!(Statistics::new(&[]).mean(), None);
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day1")).unwrap();
+        assert_eq!(part1(&input), Some(24000));
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day1")).unwrap();
+        assert_eq!(part2(&input), 45000);
+    }
 }
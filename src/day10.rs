@@ -1,6 +1,10 @@
+//! Day 10: Cathode-Ray Tube.
+
+use std::io::BufRead;
+
 use advent_of_code_ocr as aoc_ocr;
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::bail;
+use eyre::ContextCompat;
 use itertools::iproduct;
 use nom::{
     branch::alt,
@@ -10,11 +14,15 @@ use nom::{
     sequence::{preceded, tuple},
     IResult,
 };
-use smallvec::{smallvec, SmallVec};
 
-#[derive(Copy, Clone)]
-enum Instruction {
+use crate::error::{collect_lines, parse_complete, ParseError, ReadError};
+
+/// A CPU instruction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Instruction {
+    /// Takes one cycle and does nothing.
     Noop,
+    /// Takes two cycles, then adds the value to X.
     AddX(i32),
 }
 
@@ -28,51 +36,73 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
     alt((parse_noop, parse_addx))(input)
 }
 
+fn parse_line(input: &str, line: &str) -> Result<Instruction, ParseError> {
+    parse_complete(
+        10,
+        input,
+        line,
+        parse_instruction,
+        "`noop` or `addx` and a value",
+    )
+}
+
+/// Parses the CPU program.
 #[aoc_generator(day10)]
-fn generator(input: &str) -> eyre::Result<Vec<Instruction>> {
-    input
-        .lines()
-        .map(|line| match parse_instruction(line) {
-            Ok((_, i)) => Ok(i),
-            Err(e) => bail!("error parsing{:?}: {}", line, e),
-        })
-        .collect()
+pub fn generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    input.lines().map(|line| parse_line(input, line)).collect()
 }
 
-fn execute(instructions: &[Instruction]) -> impl Iterator<Item = i32> + '_ {
-    instructions
-        .iter()
-        .scan(1, |state, instruction| match instruction {
-            Instruction::Noop => Some::<SmallVec<[i32; 2]>>(smallvec![*state]),
+/// Streaming version of [`generator`].
+pub fn read(reader: impl BufRead) -> Result<Vec<Instruction>, ReadError> {
+    collect_lines(reader, |line| parse_line(line, line))
+}
+
+/// The value of X during each cycle.
+fn execute(instructions: &[Instruction]) -> eyre::Result<Vec<i32>> {
+    let mut x = 1i32;
+    let mut xs = Vec::with_capacity(instructions.len() * 2);
+    for (i, instruction) in instructions.iter().enumerate() {
+        match instruction {
+            Instruction::Noop => xs.push(x),
             Instruction::AddX(value) => {
-                let x = *state;
-                *state += value;
-                Some(smallvec![x, x])
+                xs.extend([x, x]);
+                x = x.checked_add(*value).with_context(|| {
+                    format!("instruction {} (`addx {}`) overflows X", i + 1, value)
+                })?;
             }
-        })
-        .flatten()
+        }
+    }
+
+    Ok(xs)
 }
 
+/// The summed signal strength during the 20th, 60th, ... 220th cycles.
 #[aoc(day10, part1)]
-fn part1(input: &[Instruction]) -> i32 {
-    execute(input)
+pub fn part1(input: &[Instruction]) -> eyre::Result<i32> {
+    execute(input)?
+        .into_iter()
         .enumerate()
         .skip(19)
         .step_by(40)
-        .map(|(i, x)| (i + 1) as i32 * x)
-        .sum()
+        .try_fold(0i32, |sum, (i, x)| {
+            i32::try_from(i + 1)
+                .ok()
+                .and_then(|cycle| cycle.checked_mul(x))
+                .and_then(|strength| sum.checked_add(strength))
+                .with_context(|| format!("the signal strength overflows in cycle {}", i + 1))
+        })
 }
 
-#[aoc(day10, part2)]
-fn part2(input: &[Instruction]) -> String {
-    let screen = iproduct!((0..6), (0..40)).zip(execute(input)).fold(
+/// Draws the CRT as six rows of `#` and `.`, each preceded by a newline.
+pub fn render(input: &[Instruction]) -> eyre::Result<String> {
+    let screen = iproduct!((0..6), (0..40)).zip(execute(input)?).fold(
         String::with_capacity(6 * 40 + 6),
         |mut acc, ((_, x), sprite)| {
             if x == 0 {
                 acc.push('\n');
             }
 
-            if ((sprite - 1)..=(sprite + 1)).contains(&x) {
+            if sprite.abs_diff(x) <= 1 {
                 acc.push('#');
             } else {
                 acc.push('.');
@@ -81,6 +111,118 @@ fn part2(input: &[Instruction]) -> String {
             acc
         },
     );
+    Ok(screen)
+}
+
+/// The letters drawn on the CRT.
+#[aoc(day10, part2)]
+pub fn part2(input: &[Instruction]) -> eyre::Result<String> {
+    let screen = render(input)?;
+    Ok(aoc_ocr::parse_string_to_letters(&screen))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day10")).unwrap();
+        assert_eq!(input.len(), 146);
+        assert!(matches!(input[0], Instruction::AddX(15)));
+        assert!(matches!(input[9], Instruction::Noop));
+    }
+
+    #[test]
+    fn test_read() {
+        let input = example!("day10");
+        assert_eq!(read(input.as_bytes()).unwrap(), generator(input).unwrap());
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day10")).unwrap();
+        assert_eq!(part1(&input).unwrap(), 13140);
+
+        let input = generator("addx 2147483647\naddx 2147483647").unwrap();
+        let error = part1(&input).unwrap_err();
+        assert!(error.to_string().contains("instruction 1"), "{}", error);
+        assert!(render(&input).is_err());
+
+        let mut input = vec![Instruction::AddX(i32::MAX / 2)];
+        input.extend([Instruction::Noop; 20]);
+        let error = part1(&input).unwrap_err();
+        assert!(error.to_string().contains("cycle 20"), "{}", error);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day10")).unwrap();
+        let expected = concat!(
+            "\n##..##..##..##..##..##..##..##..##..##..",
+            "\n###...###...###...###...###...###...###.",
+            "\n####....####....####....####....####....",
+            "\n#####.....#####.....#####.....#####.....",
+            "\n######......######......######......####",
+            "\n#######.......#######.......#######.....",
+        );
+        assert_eq!(render(&input).unwrap(), expected);
+    }
+
+    /// A program that draws `screen`. Each `addx` spans two pixels of a row,
+    /// and X picks which of them are lit: X = 2k lights both of pixels 2k and
+    /// 2k + 1, 2k - 1 only the first, 2k + 2 only the second, and far away
+    /// neither.
+    fn draw(screen: &str) -> Vec<Instruction> {
+        let pixels = screen.chars().filter(|&c| c != '\n').collect::<Vec<_>>();
+        let xs = pixels
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let k = (i % 20) as i32 * 2;
+                match pair {
+                    ['#', '#'] => k,
+                    ['#', '.'] => k - 1,
+                    ['.', '#'] => k + 2,
+                    _ => -5,
+                }
+            })
+            .collect::<Vec<_>>();
+        assert!(xs[0] == 0 || xs[0] == 1, "X starts at 1");
+
+        let mut x = 1;
+        xs.iter()
+            .skip(1)
+            .chain([&1])
+            .map(|&next| {
+                let value = next - x;
+                x = next;
+                Instruction::AddX(value)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_letters() {
+        let glyphs = [
+            ('E', ["####.", "#....", "###..", "#....", "#....", "####."]),
+            ('F', ["####.", "#....", "###..", "#....", "#....", "#...."]),
+            ('H', ["#..#.", "#..#.", "####.", "#..#.", "#..#.", "#..#."]),
+            ('L', ["#....", "#....", "#....", "#....", "#....", "####."]),
+        ];
+        let letters = "EFHLLHFE";
+        let screen = (0..6)
+            .map(|row| {
+                let line = letters
+                    .chars()
+                    .map(|c| glyphs.iter().find(|(g, _)| *g == c).unwrap().1[row])
+                    .collect::<String>();
+                format!("\n{}", line)
+            })
+            .collect::<String>();
 
-    aoc_ocr::parse_string_to_letters(&screen)
+        let program = draw(&screen);
+        assert_eq!(render(&program).unwrap(), screen);
+        assert_eq!(part2(&program).unwrap(), letters);
+    }
 }
@@ -1,7 +1,9 @@
+//! Day 11: Monkey in the Middle.
+
 use std::collections::VecDeque;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::bail;
+use eyre::{eyre, ContextCompat};
 use itertools::Itertools;
 use nom::{
     branch::alt,
@@ -13,15 +15,24 @@ use nom::{
     IResult,
 };
 
-#[derive(Clone, Copy, Debug)]
-enum Operation {
+use crate::error::{parse_complete, ParseError};
+
+/// How a monkey changes an item's worry level when inspecting it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    /// Adds a constant.
     Add(u64),
+    /// Multiplies by a constant.
     Multiply(u64),
+    /// Multiplies the level by itself.
     Double,
 }
 
+/// A monkey's items and throwing rules. Monkeys only come from
+/// [`generator`], which checks that every throw goes to another monkey in
+/// the same list and that every divisor is above zero.
 #[derive(Clone, Debug)]
-struct Monkey {
+pub struct Monkey {
     items: VecDeque<u64>,
     operation: Operation,
     test: u64,
@@ -31,11 +42,31 @@ struct Monkey {
 }
 
 impl Monkey {
-    fn inspect(&self, item: u64) -> u64 {
+    /// The worry levels of the items the monkey holds, in throwing order.
+    pub fn items(&self) -> &VecDeque<u64> {
+        &self.items
+    }
+
+    /// How the monkey changes the worry level of each item it inspects.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// The divisor that decides where an item is thrown.
+    pub fn test(&self) -> u64 {
+        self.test
+    }
+
+    /// The monkeys that items are thrown to when the test passes and fails.
+    pub fn targets(&self) -> (usize, usize) {
+        (self.throw_true, self.throw_false)
+    }
+
+    fn inspect(&self, item: u64) -> Option<u64> {
         match self.operation {
-            Operation::Add(n) => item + n,
-            Operation::Multiply(n) => item * n,
-            Operation::Double => item * item,
+            Operation::Add(n) => item.checked_add(n),
+            Operation::Multiply(n) => item.checked_mul(n),
+            Operation::Double => item.checked_mul(item),
         }
     }
 }
@@ -94,18 +125,45 @@ fn parse_monkey(input: &str) -> IResult<&str, Monkey> {
     Ok((input, monkey))
 }
 
+/// Parses the monkeys' starting items and throwing rules.
 #[aoc_generator(day11)]
-fn generator(input: &str) -> eyre::Result<Vec<Monkey>> {
-    input
-        .split("\n\n")
-        .map(|part| match parse_monkey(part) {
-            Ok((_, m)) => Ok(m),
-            Err(e) => bail!("error parsing{:?}: {}", part, e),
-        })
-        .collect()
+pub fn generator(input: &str) -> Result<Vec<Monkey>, ParseError> {
+    let parts = input.split("\n\n").collect::<Vec<_>>();
+    let monkeys = parts
+        .iter()
+        .map(|part| parse_complete(11, input, part, parse_monkey, "a monkey definition"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    fn after<'a>(part: &'a str, prefix: &str) -> &'a str {
+        let i = part.find(prefix).map_or(0, |i| i + prefix.len());
+        &part[i..]
+    }
+
+    for (i, (part, monkey)) in parts.iter().zip(&monkeys).enumerate() {
+        if monkey.test == 0 {
+            let position = after(part, "divisible by ");
+            return Err(ParseError::at(11, input, position, "a divisor above zero"));
+        }
+
+        let throws = [
+            ("If true: throw to monkey ", monkey.throw_true),
+            ("If false: throw to monkey ", monkey.throw_false),
+        ];
+        for (prefix, target) in throws {
+            if target >= monkeys.len() || target == i {
+                let expected = format!(
+                    "the number of another monkey, between 0 and {}",
+                    monkeys.len() - 1
+                );
+                return Err(ParseError::at(11, input, after(part, prefix), expected));
+            }
+        }
+    }
+
+    Ok(monkeys)
 }
 
-fn solve<F>(input: &[Monkey], rounds: usize, reducer: F) -> usize
+fn solve<F>(input: &[Monkey], rounds: usize, reducer: F) -> eyre::Result<usize>
 where
     F: Fn(u64) -> u64,
 {
@@ -117,9 +175,11 @@ where
                 let throw_true = monkeys[i].throw_true;
                 let throw_false = monkeys[i].throw_false;
 
-                let level = monkeys[i].inspect(item);
+                let level = monkeys[i]
+                    .inspect(item)
+                    .ok_or_else(|| eyre!("monkey {} overflowed an item's worry level", i))?;
                 let level = reducer(level);
-                if level % test == 0 {
+                if level.is_multiple_of(test) {
                     monkeys[throw_true].items.push_back(level);
                 } else {
                     monkeys[throw_false].items.push_back(level);
@@ -130,22 +190,78 @@ where
         }
     }
 
-    monkeys
-        .iter()
-        .map(|m| m.inspected)
+    monkey_business(monkeys.iter().map(|m| m.inspected))
+}
+
+/// The product of the two highest inspection counts.
+fn monkey_business(inspected: impl Iterator<Item = usize>) -> eyre::Result<usize> {
+    inspected
         .sorted()
         .rev()
         .take(2)
-        .product()
+        .try_fold(1usize, |acc, n| acc.checked_mul(n))
+        .context("the monkey business overflows")
 }
 
+/// The monkey business after 20 rounds with relief.
 #[aoc(day11, part1)]
-fn part1(input: &[Monkey]) -> usize {
+pub fn part1(input: &[Monkey]) -> eyre::Result<usize> {
     solve(input, 20, |level| level / 3)
 }
 
+/// The monkey business after 10000 rounds without relief.
 #[aoc(day11, part2)]
-fn part2(input: &[Monkey]) -> usize {
-    let product: u64 = input.iter().map(|m| m.test).product();
+pub fn part2(input: &[Monkey]) -> eyre::Result<usize> {
+    let product = input
+        .iter()
+        .try_fold(1u64, |acc, m| acc.checked_mul(m.test))
+        .context("the monkeys' divisors multiply past the largest worry level")?;
     solve(input, 10000, |level| level % product)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monkey_business() {
+        assert_eq!(
+            monkey_business([101, 95, 7, 105].into_iter()).unwrap(),
+            10605
+        );
+        assert!(monkey_business([usize::MAX, 2, 1].into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day11")).unwrap();
+        assert_eq!(input.len(), 4);
+        assert_eq!(input[0].items, [79, 98]);
+        assert!(matches!(input[0].operation, Operation::Multiply(19)));
+        assert!(matches!(input[2].operation, Operation::Double));
+        assert_eq!(input[1].test, 19);
+        assert_eq!((input[3].throw_true, input[3].throw_false), (0, 1));
+    }
+
+    #[test]
+    fn test_generator_error() {
+        let input = example!("day11").replacen("throw to monkey 2", "throw to monkey 0", 1);
+        let error = generator(&input).unwrap_err();
+        assert_eq!((error.line, error.column), (5, 30));
+
+        let input = example!("day11").replacen("throw to monkey 3", "throw to monkey 4", 1);
+        assert!(generator(&input).is_err());
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day11")).unwrap();
+        assert_eq!(part1(&input).unwrap(), 10605);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day11")).unwrap();
+        assert_eq!(part2(&input).unwrap(), 2713310158);
+    }
+}
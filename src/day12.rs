@@ -1,75 +1,82 @@
-use std::{
-    collections::{HashMap, HashSet, VecDeque},
-    iter,
-};
+//! Day 12: Hill Climbing Algorithm.
+
+use std::collections::VecDeque;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
-type Point = (i32, i32);
+use crate::{
+    error::ParseError,
+    grid::{self, GridError, Point},
+};
 
+/// The heightmap, with `S` and `E` replaced by the heights `a` and `z`.
 #[derive(Clone, Debug, Default)]
-struct Grid {
-    start: Point,
-    end: Point,
-    map: HashMap<Point, u8>,
+pub struct Grid {
+    /// Where the climb starts, marked `S`.
+    pub start: Point,
+    /// The best signal, marked `E`.
+    pub end: Point,
+    /// The height of each square, as its letter from `b'a'` to `b'z'`.
+    pub map: grid::Grid<u8>,
 }
 
+/// Parses the heightmap and its start and end positions.
 #[aoc_generator(day12)]
-fn generator(input: &str) -> Grid {
-    input
-        .lines()
-        .enumerate()
-        .fold(Grid::default(), |mut grid, (y, line)| {
-            for (x, b) in line.bytes().enumerate() {
-                let point = (x as i32, y as i32);
-
-                if b == b'S' {
-                    grid.start = point;
-                } else if b == b'E' {
-                    grid.end = point;
-                }
-
-                let b = match b {
-                    b'S' => b'a',
-                    b'E' => b'z',
-                    b => b,
-                };
-
-                grid.map.insert(point, b);
+pub fn generator(input: &str) -> Result<Grid, ParseError> {
+    let mut start = None;
+    let mut end = None;
+    let map = grid::Grid::parse(input, |point, c| {
+        let b = u8::try_from(c).ok()?;
+        match b {
+            b'S' => {
+                start = Some(point);
+                Some(b'a')
+            }
+            b'E' => {
+                end = Some(point);
+                Some(b'z')
             }
+            b'a'..=b'z' => Some(b),
+            _ => None,
+        }
+    })
+    .map_err(|e| match e {
+        GridError::InvalidCell(point) => {
+            ParseError::at_point(12, input, point, "a height a-z, `S` or `E`")
+        }
+        GridError::RaggedRow(point) => {
+            ParseError::at_point(12, input, point, "rows of equal width")
+        }
+    })?;
 
-            grid
-        })
+    let missing = |expected| ParseError::new(12, input, input.len(), expected);
+    Ok(Grid {
+        start: start.ok_or_else(|| missing("a start marked `S`"))?,
+        end: end.ok_or_else(|| missing("an end marked `E`"))?,
+        map,
+    })
 }
 
 fn find_shortest_path(grid: &Grid, start: Point) -> Option<usize> {
-    let mut path = HashMap::<Point, Point>::new();
-    let mut visited = HashSet::new();
-    visited.insert(start);
+    let mut steps = grid::Grid::new(grid.map.width(), grid.map.height(), None);
+    steps[start] = Some(0);
 
     let mut search = VecDeque::new();
     search.push_back(start);
 
-    while let Some(current @ (x, y)) = search.pop_front() {
+    while let Some(current) = search.pop_front() {
+        let current_steps = steps[current]?;
         if current == grid.end {
-            let steps = iter::successors(Some(&current), |point| path.get(point)).count() - 1;
-            return Some(steps);
+            return Some(current_steps);
         }
 
-        let current_height = grid.map[&current];
-        let neighbors = [(-1, 0), (0, 1), (1, 0), (0, -1)]
-            .iter()
-            .map(move |&(dx, dy)| (x + dx, y + dy))
-            .filter(|n| !visited.contains(n))
-            .filter(|n| grid.map.contains_key(n))
-            .filter(|n| {
-                let neighbor_height = grid.map[n];
-                neighbor_height <= current_height + 1
-            })
-            .collect::<Vec<_>>();
-        for neighbor in neighbors {
-            visited.insert(neighbor);
-            path.insert(neighbor, current);
+        let current_height = grid.map[current];
+        for neighbor in grid.map.neighbors4(current) {
+            if steps[neighbor].is_some() || grid.map[neighbor] > current_height + 1 {
+                continue;
+            }
+
+            steps[neighbor] = Some(current_steps + 1);
             search.push_back(neighbor);
         }
     }
@@ -77,19 +84,45 @@ fn find_shortest_path(grid: &Grid, start: Point) -> Option<usize> {
     None
 }
 
+/// The fewest steps from the start to the end.
 #[aoc(day12, part1)]
-fn part1(input: &Grid) -> Option<usize> {
+pub fn part1(input: &Grid) -> Option<usize> {
     find_shortest_path(input, input.start)
 }
 
+/// The fewest steps from any lowest square to the end.
 #[aoc(day12, part2)]
-fn part2(input: &Grid) -> Option<usize> {
-    let mut steps = input
+pub fn part2(input: &Grid) -> Option<usize> {
+    input
         .map
         .iter()
         .filter(|(_, &height)| height == b'a')
-        .filter_map(|(&start, _)| find_shortest_path(input, start))
-        .collect::<Vec<_>>();
-    steps.sort_unstable();
-    steps.first().copied()
+        .filter_map(|(start, _)| find_shortest_path(input, start))
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day12")).unwrap();
+        assert_eq!(input.start, (0, 0));
+        assert_eq!(input.end, (5, 2));
+        assert_eq!(input.map[(0, 0)], b'a');
+        assert_eq!(input.map[(5, 2)], b'z');
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day12")).unwrap();
+        assert_eq!(part1(&input), Some(31));
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day12")).unwrap();
+        assert_eq!(part2(&input), Some(29));
+    }
 }
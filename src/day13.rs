@@ -1,19 +1,25 @@
+//! Day 13: Distress Signal.
+
 use std::cmp::Ordering;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::bail;
 use nom::{
     branch::alt,
     character::complete::{char, line_ending, u32},
-    combinator::{complete, map},
-    multi::{separated_list0, separated_list1},
-    sequence::{delimited, separated_pair, tuple},
+    combinator::map,
+    multi::separated_list0,
+    sequence::{delimited, separated_pair},
     IResult,
 };
 
+use crate::error::{parse_complete, ParseError};
+
+/// An integer or a list of packets, ordered by the puzzle's comparison rules.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
-enum Packet {
+pub enum Packet {
+    /// A single integer.
     Value(u32),
+    /// A list of packets, compared item by item.
     List(Vec<Packet>),
 }
 
@@ -46,24 +52,37 @@ fn parse_packet(input: &str) -> IResult<&str, Packet> {
     )(input)
 }
 
-fn parse_input(input: &str) -> IResult<&str, Vec<(Packet, Packet)>> {
-    let parse_pair = separated_pair(parse_packet, line_ending, parse_packet);
-    complete(separated_list1(
-        tuple((line_ending, line_ending)),
-        parse_pair,
-    ))(input)
+fn parse_pair(input: &str) -> IResult<&str, (Packet, Packet)> {
+    separated_pair(parse_packet, line_ending, parse_packet)(input)
+}
+
+/// Splits `input` at blank lines, with either `\n` or `\r\n` line endings.
+fn paragraphs(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split("\n\n")
+        .flat_map(|chunk| chunk.split("\r\n\r\n"))
+        .map(|chunk| chunk.strip_suffix('\r').unwrap_or(chunk))
 }
 
+/// Parses the pairs of packets.
 #[aoc_generator(day13)]
-fn generator(input: &str) -> eyre::Result<Vec<(Packet, Packet)>> {
-    match parse_input(input) {
-        Ok((_, i)) => Ok(i),
-        Err(e) => bail!("error parsing: {}", e),
-    }
+pub fn generator(input: &str) -> Result<Vec<(Packet, Packet)>, ParseError> {
+    paragraphs(input)
+        .map(|pair| {
+            parse_complete(
+                13,
+                input,
+                pair,
+                parse_pair,
+                "a pair of packets like `[1,[2]]`",
+            )
+        })
+        .collect()
 }
 
+/// The summed indices of the pairs that are in the right order.
 #[aoc(day13, part1)]
-fn part1(input: &[(Packet, Packet)]) -> usize {
+pub fn part1(input: &[(Packet, Packet)]) -> usize {
     input
         .iter()
         .zip(1..)
@@ -72,8 +91,9 @@ fn part1(input: &[(Packet, Packet)]) -> usize {
         .sum()
 }
 
+/// The decoder key for the sorted packets.
 #[aoc(day13, part2)]
-fn part2(input: &[(Packet, Packet)]) -> usize {
+pub fn part2(input: &[(Packet, Packet)]) -> usize {
     let dividers = vec![
         Packet::List(vec![Packet::List(vec![Packet::Value(2)])]),
         Packet::List(vec![Packet::List(vec![Packet::Value(6)])]),
@@ -91,3 +111,35 @@ fn part2(input: &[(Packet, Packet)]) -> usize {
         .map(|divider| packets.iter().filter(|&packet| packet < divider).count() + 1)
         .product()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day13")).unwrap();
+        assert_eq!(input.len(), 8);
+        assert_eq!(
+            input[1].1,
+            Packet::List(vec![Packet::List(vec![Packet::Value(1)]), Packet::Value(4)])
+        );
+        assert_eq!(input[5].0, Packet::List(vec![]));
+
+        let crlf = example!("day13").replace('\n', "\r\n");
+        assert_eq!(generator(&crlf).unwrap(), input);
+        assert_eq!(generator(&format!("{}\r", crlf)).unwrap(), input);
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day13")).unwrap();
+        assert_eq!(part1(&input), 13);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day13")).unwrap();
+        assert_eq!(part2(&input), 140);
+    }
+}
@@ -1,92 +1,203 @@
-use std::collections::HashSet;
+//! Day 14: Regolith Reservoir.
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::eyre;
+use eyre::bail;
 use itertools::{iproduct, Itertools};
 use nom::{
     bytes::complete::tag,
-    character::complete::{char, i16, line_ending},
+    character::complete::{char, i16, u16},
     multi::separated_list1,
     sequence::separated_pair,
-    Finish, IResult,
+    IResult,
 };
 
-type Point = (i16, i16);
-type Grid = HashSet<Point>;
+use crate::{
+    error::{parse_complete, ParseError},
+    grid::{Grid, Point},
+};
 
 const DROP_POINT: Point = (500, 0);
 
-fn parse_input(input: &str) -> IResult<&str, Vec<Vec<Point>>> {
-    let parse_point = separated_pair(i16, char(','), i16);
-    let parse_line = separated_list1(tag(" -> "), parse_point);
-    separated_list1(line_ending, parse_line)(input)
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tile {
+    Air,
+    Rock,
+    Sand,
+}
+
+/// A scan of the rock in the cave, wide enough to hold all of the sand.
+#[derive(Clone, Debug)]
+pub struct Cave {
+    tiles: Grid<Tile>,
+    left: i32,
+    bottom: i32,
+}
+
+impl Cave {
+    fn is_open(&self, (x, y): Point) -> bool {
+        self.tiles.get((x - self.left, y)) == Some(&Tile::Air)
+    }
+
+    fn fill(&mut self, (x, y): Point, tile: Tile) {
+        if let Some(t) = self.tiles.get_mut((x - self.left, y)) {
+            *t = tile;
+        }
+    }
 }
 
+fn parse_path(input: &str) -> IResult<&str, Vec<(i16, u16)>> {
+    let parse_point = separated_pair(i16, char(','), u16);
+    separated_list1(tag(" -> "), parse_point)(input)
+}
+
+/// The most tiles a cave scan may hold, at one byte each. The sand spreads
+/// into a triangle as wide as twice its depth, so this allows rock about
+/// 5,800 deep.
+const MAX_TILES: usize = 1 << 26;
+
+/// Parses the rock paths into a cave scan.
 #[aoc_generator(day14)]
-fn generator(input: &str) -> eyre::Result<Grid> {
-    let lines = parse_input(input)
-        .finish()
-        .map_err(|e| eyre!("error parsing: {}", e))
-        .map(|(_, o)| o)?;
-    let grid = lines
-        .into_iter()
-        .flat_map(|line| {
-            line.into_iter()
+pub fn generator(input: &str) -> Result<Cave, ParseError> {
+    let paths = input
+        .lines()
+        .map(|line| {
+            let path = parse_complete(
+                14,
+                input,
+                line,
+                parse_path,
+                "a rock path like `498,4 -> 498,6`",
+            )?;
+            Ok((line, path))
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    let segments = paths
+        .iter()
+        .flat_map(|(line, path)| {
+            path.iter()
+                .map(|&(x, y)| (i32::from(x), i32::from(y)))
                 .tuple_windows()
-                .flat_map(|((ax, ay), (bx, by))| {
-                    let sx = ax.min(bx);
-                    let ex = ax.max(bx);
-                    let sy = ay.min(by);
-                    let ey = ay.max(by);
-                    iproduct!(sx..=ex, sy..=ey)
-                })
+                .map(move |(a, b)| (*line, a, b))
         })
-        .collect();
+        .collect::<Vec<_>>();
+
+    let (deepest, bottom) = segments
+        .iter()
+        .map(|&(line, (_, ay), (_, by))| (line, ay.max(by)))
+        .max_by_key(|&(_, y)| y)
+        .ok_or_else(|| ParseError::new(14, input, 0, "a rock path of at least two points"))?;
+    let floor = bottom + 2;
+    let (drop_x, _) = DROP_POINT;
+    let (minx, maxx) = segments
+        .iter()
+        .flat_map(|&(_, (ax, _), (bx, _))| [ax, bx])
+        .chain([drop_x - floor, drop_x + floor])
+        .minmax()
+        .into_option()
+        .unwrap_or((drop_x, drop_x));
+
+    let width = (maxx - minx + 1) as usize;
+    if width.saturating_mul(floor as usize) > MAX_TILES {
+        let expected = format!(
+            "a cave of at most {} tiles, not {} wide by {} deep",
+            MAX_TILES, width, floor
+        );
+        return Err(ParseError::at(14, input, deepest, expected));
+    }
+
+    let mut cave = Cave {
+        tiles: Grid::new(width, floor as usize, Tile::Air),
+        left: minx,
+        bottom,
+    };
+    for (_, (ax, ay), (bx, by)) in segments {
+        for rock in iproduct!(ax.min(bx)..=ax.max(bx), ay.min(by)..=ay.max(by)) {
+            cave.fill(rock, Tile::Rock);
+        }
+    }
 
-    Ok(grid)
+    Ok(cave)
 }
 
-fn simulate<P>(mut grid: Grid, goal: P) -> usize
+fn simulate<P>(mut cave: Cave, goal: P) -> eyre::Result<usize>
 where
     P: Fn(Point) -> bool,
 {
-    let maxy = grid.iter().map(|&(_, y)| y).max().unwrap();
-    let floor = maxy + 2;
-
-    for round in 1.. {
+    let mut round = 0;
+    loop {
+        round += 1;
         let mut position = DROP_POINT;
         loop {
             let (x, y) = position;
             let next_position = [(0, 1), (-1, 1), (1, 1)]
                 .iter()
                 .map(|(dx, dy)| (x + dx, y + dy))
-                .take_while(|&(_, y)| y < floor)
-                .find(|p| !grid.contains(p));
+                .find(|&p| cave.is_open(p));
             if let Some(next) = next_position {
                 position = next;
             } else {
                 if goal(position) {
-                    return round;
+                    return Ok(round);
+                }
+
+                if position == DROP_POINT {
+                    bail!("the source was blocked after {} units of sand", round);
                 }
 
-                grid.insert(position);
+                cave.fill(position, Tile::Sand);
                 break;
             }
         }
     }
-
-    unreachable!()
 }
 
+/// The units of sand that come to rest before sand starts flowing into the abyss.
 #[aoc(day14, part1)]
-fn part1(input: &Grid) -> usize {
-    let grid = input.clone();
-    let goal = grid.iter().map(|&(_, y)| y).max().unwrap();
-    simulate(grid, |(_, y)| y >= goal) - 1
+pub fn part1(input: &Cave) -> eyre::Result<usize> {
+    let goal = input.bottom;
+    Ok(simulate(input.clone(), |(_, y)| y >= goal)? - 1)
 }
 
+/// The units of sand that come to rest before the source is blocked.
 #[aoc(day14, part2)]
-fn part2(input: &Grid) -> usize {
-    let grid = input.clone();
-    simulate(grid, |point| point == DROP_POINT)
+pub fn part2(input: &Cave) -> eyre::Result<usize> {
+    simulate(input.clone(), |point| point == DROP_POINT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day14")).unwrap();
+        assert_eq!(input.bottom, 9);
+        assert!(!input.is_open((498, 4)));
+        assert!(!input.is_open((494, 9)));
+        assert!(input.is_open((500, 8)));
+
+        let error = generator("500,-1 -> 500,3").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 5));
+
+        let error = generator("498,4 -> 498,6\n500,0 -> 500,60000").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert!(error.expected.starts_with("a cave of at most"), "{}", error);
+        assert!(generator("-32768,0 -> 32767,65535").is_err());
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day14")).unwrap();
+        assert_eq!(part1(&input).unwrap(), 24);
+
+        // A lid over the source traps the sand before it reaches the abyss.
+        let input = generator("498,1 -> 502,1").unwrap();
+        assert!(part1(&input).is_err());
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day14")).unwrap();
+        assert_eq!(part2(&input).unwrap(), 93);
+    }
 }
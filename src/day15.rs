@@ -1,22 +1,28 @@
-use std::collections::{HashMap, HashSet};
+//! Day 15: Beacon Exclusion Zone.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufRead,
+};
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::eyre;
 use itertools::{iproduct, Itertools};
 use nom::{
     bytes::complete::tag,
-    character::complete::{i64, line_ending},
+    character::complete::i32,
     combinator::map,
-    multi::separated_list1,
     sequence::{preceded, separated_pair, tuple},
-    Finish, IResult,
+    IResult,
 };
 
+use crate::error::{collect_lines, parse_complete, ParseError, ReadError};
+
 const TARGET_ROW: i64 = 2000000;
 const LIMIT: i64 = 4000000;
 
-#[derive(Clone, Copy, Eq, Hash, PartialEq)]
-struct Point(i64, i64);
+/// An `(x, y)` position on the sensor map.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Point(pub i64, pub i64);
 
 impl Point {
     fn distance(&self, Point(bx, by): Point) -> i64 {
@@ -40,19 +46,21 @@ impl From<Point> for RotatedPoint {
     }
 }
 
+/// Parses a point. Coordinates are limited to 32 bits, so distances and
+/// rotated coordinates between them cannot overflow.
 fn parse_point(input: &str) -> IResult<&str, Point> {
     map(
         separated_pair(
-            preceded(tag("x="), i64),
+            preceded(tag("x="), i32),
             tag(", "),
-            preceded(tag("y="), i64),
+            preceded(tag("y="), i32),
         ),
-        |(x, y)| Point(x, y),
+        |(x, y)| Point(x.into(), y.into()),
     )(input)
 }
 
-fn parse_input(input: &str) -> IResult<&str, HashMap<Point, Point>> {
-    let line = map(
+fn parse_report(input: &str) -> IResult<&str, (Point, Point)> {
+    map(
         tuple((
             tag("Sensor at "),
             parse_point,
@@ -60,43 +68,65 @@ fn parse_input(input: &str) -> IResult<&str, HashMap<Point, Point>> {
             parse_point,
         )),
         |(_, a, _, b)| (a, b),
-    );
+    )(input)
+}
 
-    map(separated_list1(line_ending, line), HashMap::from_iter)(input)
+fn parse_line(input: &str, line: &str) -> Result<(Point, Point), ParseError> {
+    parse_complete(
+        15,
+        input,
+        line,
+        parse_report,
+        "a report like `Sensor at x=2, y=18: closest beacon is at x=-2, y=15`",
+    )
 }
 
+/// Parses each sensor and its closest beacon.
 #[aoc_generator(day15)]
-fn generator(input: &str) -> eyre::Result<HashMap<Point, Point>> {
-    parse_input(input)
-        .finish()
-        .map_err(|e| eyre!("error parsing: {}", e))
-        .map(|(_, o)| o)
+pub fn generator(input: &str) -> Result<HashMap<Point, Point>, ParseError> {
+    input.lines().map(|line| parse_line(input, line)).collect()
 }
 
-#[aoc(day15, part1)]
-fn part1(input: &HashMap<Point, Point>) -> usize {
-    let beacons = input.iter().map(|(_, &b)| b).collect::<HashSet<Point>>();
-    let distances = input
-        .iter()
-        .map(|(&s, &b)| (s, s.distance(b)))
-        .collect::<HashMap<Point, i64>>();
-    let (minx, maxx) = distances
+/// Streaming version of [`generator`].
+pub fn read(reader: impl BufRead) -> Result<HashMap<Point, Point>, ReadError> {
+    collect_lines(reader, |line| parse_line(line, line))
+}
+
+/// The number of positions in `row` where a beacon cannot be.
+pub fn count_covered(input: &HashMap<Point, Point>, row: i64) -> usize {
+    // Each sensor covers one run of the row; merge the runs and count them.
+    let mut runs = input
         .iter()
-        .flat_map(|(Point(x, _), d)| [x - d, x + d])
-        .minmax()
-        .into_option()
-        .unwrap();
-
-    (minx..=maxx)
-        .filter(|&tx| {
-            let t = Point(tx, TARGET_ROW);
-            !beacons.contains(&t) && distances.iter().any(|(&s, &d)| s.distance(t) <= d)
+        .filter_map(|(&s, &b)| {
+            let Point(x, y) = s;
+            let spare = s.distance(b) - (y - row).abs();
+            (spare >= 0).then_some((x - spare, x + spare))
         })
-        .count()
+        .collect::<Vec<_>>();
+    runs.sort_unstable();
+
+    let mut covered = vec![];
+    for (start, end) in runs {
+        match covered.last_mut() {
+            Some((_, last)) if start <= *last + 1 => *last = end.max(*last),
+            _ => covered.push((start, end)),
+        }
+    }
+
+    let beacons = input
+        .values()
+        .filter(|&&Point(x, y)| y == row && covered.iter().any(|&(s, e)| (s..=e).contains(&x)))
+        .collect::<HashSet<_>>();
+    let total = covered
+        .iter()
+        .map(|(start, end)| end - start + 1)
+        .sum::<i64>();
+    total as usize - beacons.len()
 }
 
-#[aoc(day15, part2)]
-fn part2(input: &HashMap<Point, Point>) -> Option<i64> {
+/// The tuning frequency of the only uncovered position with both coordinates
+/// between 0 and `limit`.
+pub fn find_tuning_frequency(input: &HashMap<Point, Point>, limit: i64) -> Option<i64> {
     let distances = input
         .iter()
         .map(|(&s, &b)| (s, s.distance(b)))
@@ -122,7 +152,66 @@ fn part2(input: &HashMap<Point, Point>) -> Option<i64> {
         .collect::<HashSet<_>>();
     iproduct!(searchx.iter(), searchy.iter())
         .map(|(&rx, &ry)| Point::from(RotatedPoint(rx, ry)))
-        .filter(|&Point(x, y)| (0..=LIMIT).contains(&x) && (0..=LIMIT).contains(&y))
+        .filter(|&Point(x, y)| (0..=limit).contains(&x) && (0..=limit).contains(&y))
         .find(|&p| distances.iter().all(|(&s, &d)| s.distance(p) > d))
         .map(|Point(x, y)| x * 4000000 + y)
 }
+
+/// The number of positions in row 2000000 where a beacon cannot be.
+#[aoc(day15, part1)]
+pub fn part1(input: &HashMap<Point, Point>) -> usize {
+    count_covered(input, TARGET_ROW)
+}
+
+/// The tuning frequency of the distress beacon.
+#[aoc(day15, part2)]
+pub fn part2(input: &HashMap<Point, Point>) -> Option<i64> {
+    find_tuning_frequency(input, LIMIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day15")).unwrap();
+        assert_eq!(input.len(), 14);
+        assert_eq!(input[&Point(2, 18)], Point(-2, 15));
+
+        let extreme = "Sensor at x=-9223372036854775808, y=0: \
+                       closest beacon is at x=9223372036854775807, y=0";
+        let error = generator(extreme).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 13));
+    }
+
+    #[test]
+    fn test_read() {
+        let input = example!("day15");
+        assert_eq!(read(input.as_bytes()).unwrap(), generator(input).unwrap());
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day15")).unwrap();
+        assert_eq!(count_covered(&input, 10), 26);
+        assert_eq!(count_covered(&HashMap::new(), 10), 0);
+
+        for row in -15..=35 {
+            let expected = (-20..=40)
+                .map(|x| Point(x, row))
+                .filter(|&t| {
+                    !input.values().any(|&b| b == t)
+                        && input.iter().any(|(&s, &b)| s.distance(t) <= s.distance(b))
+                })
+                .count();
+            assert_eq!(count_covered(&input, row), expected, "row {}", row);
+        }
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day15")).unwrap();
+        assert_eq!(find_tuning_frequency(&input, 20), Some(56000011));
+    }
+}
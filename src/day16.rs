@@ -1,7 +1,9 @@
+//! Day 16: Proboscidea Volcanium.
+
 use std::{collections::HashMap, iter};
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::eyre;
+use eyre::ContextCompat;
 use itertools::{iproduct, Itertools};
 use nom::{
     branch::alt,
@@ -10,20 +12,54 @@ use nom::{
     combinator::map,
     multi::separated_list1,
     sequence::tuple,
-    Finish, IResult,
+    IResult,
 };
 use smallvec::{smallvec, SmallVec};
 
+use crate::error::{parse_complete, ParseError};
+
 const START: &str = "AA";
 
+/// The highest total flow rate, so that 30 minutes of every valve's flow
+/// still fits in a `u32`.
+const MAX_TOTAL_RATE: u32 = u32::MAX / 30;
+
+/// The valves, indexed in input order. Only [`generator`] builds these, so
+/// the distances always cover every pair of valves.
 #[derive(Debug, Default)]
-struct Input {
+pub struct Input {
     names: Vec<String>,
     flow_rates: Vec<u32>,
     connections: Vec<Vec<String>>,
     distances: HashMap<(usize, usize), u8>,
 }
 
+impl Input {
+    /// Each valve's name.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Each valve's flow rate, in pressure per minute.
+    pub fn flow_rates(&self) -> &[u32] {
+        &self.flow_rates
+    }
+
+    /// The names of the valves each valve has tunnels to.
+    pub fn connections(&self) -> &[Vec<String>] {
+        &self.connections
+    }
+
+    /// The shortest travel time from valve `from` to valve `to`, or `None` if
+    /// there is no way there.
+    pub fn distance(&self, from: usize, to: usize) -> Option<u8> {
+        self.distances
+            .get(&(from, to))
+            .copied()
+            .filter(|&d| d != u8::MAX)
+    }
+}
+
 fn parse_valve(input: &str) -> IResult<&str, (String, u32, Vec<String>)> {
     let tunnels = separated_list1(tag(", "), map(alpha1, String::from));
 
@@ -49,9 +85,9 @@ fn compute_distances(names: &[String], connections: &[Vec<String>]) -> HashMap<(
         .iter()
         .enumerate()
         .flat_map(|(i, _)| {
-            iter::once(((i, i), 0)).chain(connections[i].iter().map(move |connection| {
-                let j = names.iter().position(|n| n == connection).unwrap();
-                ((i, j), 1)
+            iter::once(((i, i), 0)).chain(connections[i].iter().filter_map(move |connection| {
+                let j = names.iter().position(|n| n == connection)?;
+                Some(((i, j), 1))
             }))
         })
         .collect::<HashMap<(usize, usize), u8>>();
@@ -69,29 +105,58 @@ fn compute_distances(names: &[String], connections: &[Vec<String>]) -> HashMap<(
     distances
 }
 
+/// Parses the valves and computes the distances between them.
 #[aoc_generator(day16)]
-fn generator(input: &str) -> eyre::Result<Input> {
+pub fn generator(input: &str) -> Result<Input, ParseError> {
     let mut result = input
         .lines()
         .map(|line| {
-            parse_valve(line)
-                .finish()
-                .map_err(|e| eyre!("Error parsing {}: {}", line, e))
-                .map(|(_, o)| o)
+            parse_complete(
+                16,
+                input,
+                line,
+                parse_valve,
+                "a valve like `Valve AA has flow rate=0; tunnels lead to valves DD, II`",
+            )
         })
         .try_fold(Input::default(), |mut acc, item| {
             let (name, flow_rate, connections) = item?;
             acc.names.push(name);
             acc.flow_rates.push(flow_rate);
             acc.connections.push(connections);
-            Ok::<_, eyre::Report>(acc)
+            Ok::<_, ParseError>(acc)
         })?;
+
+    for (line, connections) in input.lines().zip(&result.connections) {
+        if let Some(unknown) = connections.iter().find(|c| !result.names.contains(c)) {
+            // The tunnel list ends the line, so the last match is the tunnel.
+            let i = line.rfind(unknown.as_str()).unwrap_or_default();
+            return Err(ParseError::at(
+                16,
+                input,
+                &line[i..],
+                "a tunnel to a listed valve",
+            ));
+        }
+    }
+
+    let mut total_rate = 0u32;
+    for (line, &flow_rate) in input.lines().zip(&result.flow_rates) {
+        total_rate = total_rate.saturating_add(flow_rate);
+        if total_rate > MAX_TOTAL_RATE {
+            let i = line.find('=').map_or(0, |i| i + 1);
+            let expected = format!("flow rates adding up to at most {}", MAX_TOTAL_RATE);
+            return Err(ParseError::at(16, input, &line[i..], expected));
+        }
+    }
+
     result.distances = compute_distances(&result.names, &result.connections);
     Ok(result)
 }
 
 fn search(
     input: &Input,
+    start: usize,
     current: usize,
     candidates: Vec<usize>,
     time: u8,
@@ -113,6 +178,7 @@ fn search(
             let next_rate = input.flow_rates[next] * next_time_remaining as u32;
             let future_rate = search(
                 input,
+                start,
                 next,
                 next_candidates,
                 next_time_remaining,
@@ -124,8 +190,7 @@ fn search(
             results.push(answer);
 
             if elephant {
-                let start = input.names.iter().position(|n| n == "AA").unwrap();
-                let answer = search(input, start, candidates.clone(), 26, false, cache);
+                let answer = search(input, start, start, candidates.clone(), 26, false, cache);
                 results.push(answer);
             }
 
@@ -137,18 +202,76 @@ fn search(
     result
 }
 
+fn find_start(input: &Input) -> eyre::Result<usize> {
+    input
+        .names
+        .iter()
+        .position(|n| n == START)
+        .with_context(|| format!("there is no valve {} to start from", START))
+}
+
+/// The most pressure that can be released alone in 30 minutes.
 #[aoc(day16, part1)]
-fn part1(input: &Input) -> u32 {
+pub fn part1(input: &Input) -> eyre::Result<u32> {
     let candidates = input.flow_rates.iter().positions(|&r| r > 0).collect_vec();
-    let start = input.names.iter().position(|n| n == START).unwrap();
+    let start = find_start(input)?;
     let mut cache = HashMap::new();
-    search(input, start, candidates, 30, false, &mut cache)
+    Ok(search(
+        input, start, start, candidates, 30, false, &mut cache,
+    ))
 }
 
+/// The most pressure that can be released with an elephant in 26 minutes.
 #[aoc(day16, part2)]
-fn part2(input: &Input) -> u32 {
+pub fn part2(input: &Input) -> eyre::Result<u32> {
     let candidates = input.flow_rates.iter().positions(|&r| r > 0).collect_vec();
-    let start = input.names.iter().position(|n| n == START).unwrap();
+    let start = find_start(input)?;
     let mut cache = HashMap::new();
-    search(input, start, candidates, 26, true, &mut cache)
+    Ok(search(
+        input, start, start, candidates, 26, true, &mut cache,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day16")).unwrap();
+        assert_eq!(input.names.len(), 10);
+        assert_eq!(input.flow_rates[1], 13);
+        assert_eq!(input.connections[0], ["DD", "II", "BB"]);
+        assert_eq!(input.distances[&(0, 7)], 5);
+    }
+
+    #[test]
+    fn test_generator_error() {
+        let input = "Valve AA has flow rate=0; tunnels lead to valves AA, ZZ";
+        let error = generator(input).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 54));
+
+        let input = "Valve AA has flow rate=4000000000; tunnel leads to valve AA";
+        let error = generator(input).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 24));
+        let input = "Valve AA has flow rate=100000000; tunnel leads to valve BB\n\
+                     Valve BB has flow rate=100000000; tunnel leads to valve AA";
+        let error = generator(input).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 24));
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day16")).unwrap();
+        assert_eq!(part1(&input).unwrap(), 1651);
+
+        let input = generator("Valve BB has flow rate=1; tunnel leads to valve BB").unwrap();
+        assert!(part1(&input).is_err());
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day16")).unwrap();
+        assert_eq!(part2(&input).unwrap(), 1707);
+    }
 }
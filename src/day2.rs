@@ -1,116 +1,575 @@
+//! Day 2: Rock Paper Scissors.
+
+use std::{collections::HashMap, io::BufRead};
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::ContextCompat;
+use eyre::{bail, ensure, eyre, ContextCompat};
+use nom::{
+    character::complete::{char, one_of},
+    sequence::separated_pair,
+};
+
+use crate::{
+    error::{collect_lines, parse_complete, ParseError, ReadError},
+    interval::{Interval, IntervalSet},
+};
 
-#[derive(Debug, Copy, Clone)]
-enum Outcome {
+/// The result of a round, from your point of view.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Outcome {
+    /// You lost the round.
     Loss = 0,
+    /// Both players made the same move.
     Draw = 3,
+    /// You won the round.
     Win = 6,
 }
 
 impl Outcome {
-    fn score(self) -> usize {
+    /// The puzzle's score for this outcome.
+    pub fn score(self) -> usize {
         self as usize
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-enum Play {
-    Rock = 0,
-    Paper = 1,
-    Scissors = 2,
+/// A hand game with an odd number of moves arranged in a cycle, where each
+/// move beats the half of the other moves just before it. With three moves
+/// this is rock-paper-scissors; with five, rock-Spock-paper-lizard-scissors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CyclicGame {
+    moves: usize,
 }
 
-impl From<i8> for Play {
-    fn from(play: i8) -> Self {
-        match play {
-            0 => Play::Rock,
-            1 => Play::Paper,
-            2 => Play::Scissors,
-            _ => unreachable!(),
+impl CyclicGame {
+    /// The three-move game of the puzzle.
+    pub const ROCK_PAPER_SCISSORS: CyclicGame = CyclicGame { moves: 3 };
+
+    /// A game of `moves` moves, which must be odd so that every pair of
+    /// different moves has a winner.
+    pub fn new(moves: usize) -> eyre::Result<Self> {
+        ensure!(
+            moves % 2 == 1,
+            "a cyclic game needs an odd number of moves, not {}",
+            moves
+        );
+        Ok(Self { moves })
+    }
+
+    /// The number of moves in the game.
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// The outcome of playing move `you` against move `opponent`.
+    pub fn outcome(&self, you: usize, opponent: usize) -> Outcome {
+        let distance = (you + self.moves - opponent % self.moves) % self.moves;
+        match distance {
+            0 => Outcome::Draw,
+            d if d <= self.moves / 2 => Outcome::Win,
+            _ => Outcome::Loss,
         }
     }
+
+    /// Every move that reaches `outcome` against `opponent`.
+    pub fn responses(&self, opponent: usize, outcome: Outcome) -> impl Iterator<Item = usize> {
+        let game = *self;
+        (0..game.moves).filter(move |&you| game.outcome(you, opponent) == outcome)
+    }
 }
 
-impl From<char> for Play {
-    fn from(play: char) -> Self {
+/// The points for a round: one score per move, plus one per outcome.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scoring {
+    /// The points for playing each move.
+    pub moves: Vec<usize>,
+    /// The points for each outcome. Missing outcomes score nothing.
+    pub outcomes: HashMap<Outcome, usize>,
+}
+
+impl Scoring {
+    /// The puzzle's scoring: 1 point for the first move, 2 for the second and
+    /// so on, plus [`Outcome::score`].
+    pub fn standard(moves: usize) -> Self {
+        Self {
+            moves: (1..=moves).collect(),
+            outcomes: [Outcome::Loss, Outcome::Draw, Outcome::Win]
+                .into_iter()
+                .map(|outcome| (outcome, outcome.score()))
+                .collect(),
+        }
+    }
+
+    fn round(&self, you: usize, outcome: Outcome) -> usize {
+        self.moves[you] + self.outcomes.get(&outcome).copied().unwrap_or_default()
+    }
+}
+
+/// What the second column of the strategy guide means.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Response {
+    /// The move to play.
+    Move(HashMap<char, usize>),
+    /// The outcome to reach. When several moves reach it, the one that scores
+    /// the most is played.
+    Outcome(HashMap<char, Outcome>),
+}
+
+/// A complete configuration for scoring a strategy guide.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    game: CyclicGame,
+    opponent: HashMap<char, usize>,
+    response: Response,
+    scoring: Scoring,
+}
+
+impl Rules {
+    /// Rules for `game`, checking that every symbol names one of its moves and
+    /// that `scoring` has a score for each move.
+    pub fn new(
+        game: CyclicGame,
+        opponent: HashMap<char, usize>,
+        response: Response,
+        scoring: Scoring,
+    ) -> eyre::Result<Self> {
+        let mut moves = opponent.values().chain(match &response {
+            Response::Move(moves) => moves.values().collect::<Vec<_>>(),
+            Response::Outcome(_) => vec![],
+        });
+        if let Some(m) = moves.find(|&&m| m >= game.moves) {
+            bail!("move {} is outside a {}-move game", m, game.moves);
+        }
+        ensure!(
+            scoring.moves.len() == game.moves,
+            "expected a score for each of the {} moves, found {}",
+            game.moves,
+            scoring.moves.len()
+        );
+
+        Ok(Self {
+            game,
+            opponent,
+            response,
+            scoring,
+        })
+    }
+
+    /// Rock-paper-scissors where `X`-`Z` is the move to play.
+    pub fn by_move() -> Self {
+        Self {
+            game: CyclicGame::ROCK_PAPER_SCISSORS,
+            opponent: symbols("ABC"),
+            response: Response::Move(symbols("XYZ")),
+            scoring: Scoring::standard(3),
+        }
+    }
+
+    /// Rock-paper-scissors where `X`-`Z` is the outcome to reach.
+    pub fn by_outcome() -> Self {
+        let outcomes = [
+            ('X', Outcome::Loss),
+            ('Y', Outcome::Draw),
+            ('Z', Outcome::Win),
+        ];
+        Self {
+            response: Response::Outcome(outcomes.into_iter().collect()),
+            ..Self::by_move()
+        }
+    }
+
+    /// Parses a strategy guide written with this configuration's symbols.
+    pub fn parse(&self, input: &str) -> Result<Vec<(char, char)>, ParseError> {
+        let opponent = self.opponent.keys().collect::<String>();
+        let response = match &self.response {
+            Response::Move(moves) => moves.keys().collect::<String>(),
+            Response::Outcome(outcomes) => outcomes.keys().collect::<String>(),
+        };
+
+        input
+            .lines()
+            .map(|line| {
+                let round = separated_pair(one_of(&*opponent), char(' '), one_of(&*response));
+                parse_complete(2, input, line, round, "a round of known symbols")
+            })
+            .collect()
+    }
+
+    /// The move played and the outcome of one round of the guide.
+    pub fn play(&self, opponent: char, response: char) -> eyre::Result<(usize, Outcome)> {
+        let opponent = *self
+            .opponent
+            .get(&opponent)
+            .with_context(|| format!("unknown opponent move {:?}", opponent))?;
+        let you = match &self.response {
+            Response::Move(moves) => moves.get(&response).copied(),
+            Response::Outcome(outcomes) => outcomes.get(&response).and_then(|&outcome| {
+                self.game
+                    .responses(opponent, outcome)
+                    .max_by_key(|&you| (self.scoring.moves[you], usize::MAX - you))
+            }),
+        }
+        .with_context(|| format!("unknown response {:?}", response))?;
+
+        Ok((you, self.game.outcome(you, opponent)))
+    }
+
+    /// The total score of following the guide.
+    pub fn score(&self, guide: &[(char, char)]) -> eyre::Result<usize> {
+        guide
+            .iter()
+            .map(|&(opponent, response)| {
+                let (you, outcome) = self.play(opponent, response)?;
+                Ok(self.scoring.round(you, outcome))
+            })
+            .sum()
+    }
+}
+
+/// Maps each symbol to its position in `symbols`, for building [`Rules`].
+pub fn symbols(symbols: &str) -> HashMap<char, usize> {
+    symbols.chars().enumerate().map(|(i, c)| (c, i)).collect()
+}
+
+/// A rock-paper-scissors move.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Play {
+    /// Rock, which beats scissors.
+    Rock = 0,
+    /// Paper, which beats rock.
+    Paper = 1,
+    /// Scissors, which beats paper.
+    Scissors = 2,
+}
+
+impl TryFrom<char> for Play {
+    type Error = eyre::Report;
+
+    fn try_from(play: char) -> eyre::Result<Self> {
         match play {
-            'A' | 'X' => Play::Rock,
-            'B' | 'Y' => Play::Paper,
-            'C' | 'Z' => Play::Scissors,
-            _ => unreachable!(),
+            'A' | 'X' => Ok(Play::Rock),
+            'B' | 'Y' => Ok(Play::Paper),
+            'C' | 'Z' => Ok(Play::Scissors),
+            _ => Err(eyre!("expected a play A-C or X-Z, found {:?}", play)),
         }
     }
 }
 
 impl Play {
-    fn find_loss(self) -> Play {
-        let value = self as i8;
-        let loss = (value - 1).rem_euclid(3);
-        Play::from(loss)
+    /// Every play, in the order of their scores.
+    pub const ALL: [Play; 3] = [Play::Rock, Play::Paper, Play::Scissors];
+
+    fn respond(self, outcome: Outcome) -> Play {
+        CyclicGame::ROCK_PAPER_SCISSORS
+            .responses(self as usize, outcome)
+            .next()
+            .map_or(self, |i| Play::ALL[i])
     }
 
-    fn find_win(self) -> Play {
-        let value = self as i8;
-        let win = (value + 1).rem_euclid(3);
-        Play::from(win)
+    /// The play that loses to this one.
+    pub fn find_loss(self) -> Play {
+        self.respond(Outcome::Loss)
     }
 
-    fn play(self, other: Play) -> Outcome {
-        match (self, other) {
-            (a, b) if a == b => Outcome::Draw,
-            (a, b) if a.find_win() == b => Outcome::Loss,
-            (a, b) if a.find_loss() == b => Outcome::Win,
-            _ => unreachable!(),
-        }
+    /// The play that beats this one.
+    pub fn find_win(self) -> Play {
+        self.respond(Outcome::Win)
     }
 
-    fn score(self) -> usize {
+    /// The outcome of playing this against `other`.
+    pub fn play(self, other: Play) -> Outcome {
+        CyclicGame::ROCK_PAPER_SCISSORS.outcome(self as usize, other as usize)
+    }
+
+    /// The points for choosing this play.
+    pub fn score(self) -> usize {
         (self as usize) + 1
     }
 }
 
-#[aoc_generator(day2)]
-fn generator(input: &str) -> eyre::Result<Vec<(char, char)>> {
-    input
-        .lines()
-        .map(|line| {
-            let mut chars = line.chars();
-            let left = chars.next().context("getting left")?;
-            let right = chars.nth(1).context("getting right")?;
-            Ok((left, right))
-        })
+/// The plays open to you against `opponent`, with what each would score.
+fn choices(opponent: Play) -> [(Play, usize); 3] {
+    Play::ALL.map(|play| (play, play.score() + play.play(opponent).score()))
+}
+
+/// The opponent's column of a strategy guide.
+pub fn opponents(guide: &[(char, char)]) -> eyre::Result<Vec<Play>> {
+    guide
+        .iter()
+        .map(|&(opponent, _)| Play::try_from(opponent))
         .collect()
 }
 
-#[aoc(day2, part1)]
-fn part1(input: &[(char, char)]) -> usize {
-    input
+/// The highest total you can score against `opponents`.
+pub fn best_score(opponents: &[Play]) -> usize {
+    opponents
         .iter()
-        .map(|&(opponent, you)| {
-            let opponent = Play::from(opponent);
-            let you = Play::from(you);
-            let outcome = you.play(opponent);
-            you.score() + outcome.score()
-        })
+        .filter_map(|&opponent| choices(opponent).iter().map(|&(_, s)| s).max())
         .sum()
 }
 
-#[aoc(day2, part2)]
-fn part2(input: &[(char, char)]) -> usize {
-    input
+/// The lowest total you can score against `opponents`.
+pub fn worst_score(opponents: &[Play]) -> usize {
+    opponents
         .iter()
-        .map(|&(opponent, you)| {
-            let opponent = Play::from(opponent);
-            let you = match you {
-                'X' => opponent.find_loss(),
-                'Y' => opponent,
-                'Z' => opponent.find_win(),
-                _ => unreachable!(),
-            };
+        .filter_map(|&opponent| choices(opponent).iter().map(|&(_, s)| s).min())
+        .sum()
+}
 
-            let outcome = you.play(opponent);
-            you.score() + outcome.score()
+/// The totals reachable after one more round against `opponent`, given
+/// those reachable before it.
+fn play_round(previous: &IntervalSet, opponent: Play) -> IntervalSet {
+    choices(opponent)
+        .into_iter()
+        .flat_map(|(_, score)| {
+            let score = score as u32;
+            previous
+                .iter()
+                .filter_map(move |i| Interval::new(i.start() + score, i.end() + score))
         })
-        .sum()
+        .collect()
+}
+
+/// The plays that score exactly `target` against `opponents`.
+pub fn plan(opponents: &[Play], target: usize) -> eyre::Result<Vec<Play>> {
+    let (worst, best) = (worst_score(opponents), best_score(opponents));
+    if !(worst..=best).contains(&target) {
+        bail!(
+            "a score of {} cannot be reached; totals range from {} to {}",
+            target,
+            worst,
+            best
+        );
+    }
+
+    ensure!(
+        u32::try_from(best).is_ok(),
+        "a best score of {} is too high to plan for",
+        best
+    );
+    let target = target as u32;
+
+    // reachable[i] is the set of totals reachable after the first i rounds.
+    // Away from the worst and best totals every score is reachable, so each
+    // set is a handful of intervals.
+    let mut reachable = vec![IntervalSet::from_iter(Interval::new(0, 0))];
+    for &opponent in opponents {
+        let next = play_round(&reachable[reachable.len() - 1], opponent);
+        reachable.push(next);
+    }
+
+    if !reachable[opponents.len()].contains(target) {
+        bail!(
+            "a score of {} cannot be reached, although totals range from {} to {}",
+            target,
+            worst,
+            best
+        );
+    }
+
+    let mut remaining = target;
+    let mut plays = vec![];
+    for (i, &opponent) in opponents.iter().enumerate().rev() {
+        let (play, score) = choices(opponent)
+            .into_iter()
+            .map(|(play, score)| (play, score as u32))
+            .find(|&(_, score)| score <= remaining && reachable[i].contains(remaining - score))
+            .with_context(|| format!("no play in round {} reaches the target", i + 1))?;
+        plays.push(play);
+        remaining -= score;
+    }
+
+    plays.reverse();
+    Ok(plays)
+}
+
+/// How to write the second column of a generated guide.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Column {
+    /// `X`-`Z` is the play to make, as in part 1.
+    Move,
+    /// `X`-`Z` is the outcome to reach, as in part 2.
+    Outcome,
+}
+
+/// Writes a strategy guide that makes `plays` against `opponents`.
+pub fn write_guide(opponents: &[Play], plays: &[Play], column: Column) -> Vec<(char, char)> {
+    let symbol = |base: u8, offset: usize| char::from(base + offset as u8);
+    opponents
+        .iter()
+        .zip(plays)
+        .map(|(&opponent, &play)| {
+            let response = match column {
+                Column::Move => symbol(b'X', play as usize),
+                Column::Outcome => symbol(b'X', play.play(opponent).score() / 3),
+            };
+            (symbol(b'A', opponent as usize), response)
+        })
+        .collect()
+}
+
+fn parse_round(input: &str, line: &str) -> Result<(char, char), ParseError> {
+    let round = separated_pair(one_of("ABC"), char(' '), one_of("XYZ"));
+    parse_complete(2, input, line, round, "a round like `A Y`")
+}
+
+/// Parses each round as the opponent's column (`A`-`C`) and the second column (`X`-`Z`).
+#[aoc_generator(day2)]
+pub fn generator(input: &str) -> Result<Vec<(char, char)>, ParseError> {
+    input.lines().map(|line| parse_round(input, line)).collect()
+}
+
+/// Streaming version of [`generator`].
+pub fn read(reader: impl BufRead) -> Result<Vec<(char, char)>, ReadError> {
+    collect_lines(reader, |line| parse_round(line, line))
+}
+
+/// The total score when the second column is the play to make.
+#[aoc(day2, part1)]
+pub fn part1(input: &[(char, char)]) -> eyre::Result<usize> {
+    Rules::by_move().score(input)
+}
+
+/// The total score when the second column is the outcome to reach.
+#[aoc(day2, part2)]
+pub fn part2(input: &[(char, char)]) -> eyre::Result<usize> {
+    Rules::by_outcome().score(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day2")).unwrap();
+        assert_eq!(input, vec![('A', 'Y'), ('B', 'X'), ('C', 'Z')]);
+    }
+
+    #[test]
+    fn test_read() {
+        let input = example!("day2");
+        assert_eq!(read(input.as_bytes()).unwrap(), generator(input).unwrap());
+    }
+
+    #[test]
+    fn test_play() {
+        assert_eq!(Play::Rock.find_win(), Play::Paper);
+        assert_eq!(Play::Rock.find_loss(), Play::Scissors);
+        assert_eq!(Play::Scissors.play(Play::Paper), Outcome::Win);
+        assert_eq!(Play::Scissors.play(Play::Rock), Outcome::Loss);
+    }
+
+    #[test]
+    fn test_cyclic_game() {
+        assert!(CyclicGame::new(4).is_err());
+
+        // Rock, Spock, paper, lizard, scissors.
+        let game = CyclicGame::new(5).unwrap();
+        assert_eq!(game.outcome(0, 4), Outcome::Win);
+        assert_eq!(game.outcome(0, 3), Outcome::Win);
+        assert_eq!(game.outcome(0, 1), Outcome::Loss);
+        assert_eq!(game.outcome(3, 1), Outcome::Win);
+        assert_eq!(game.responses(0, Outcome::Win).collect::<Vec<_>>(), [1, 2]);
+
+        let game = CyclicGame::new(7).unwrap();
+        for a in 0..7 {
+            let wins = (0..7).filter(|&b| game.outcome(a, b) == Outcome::Win);
+            assert_eq!(wins.count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let game = CyclicGame::new(5).unwrap();
+        let scoring = Scoring::standard(5);
+        let response = Response::Outcome(
+            [
+                ('L', Outcome::Loss),
+                ('D', Outcome::Draw),
+                ('W', Outcome::Win),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let rules = Rules::new(game, symbols("RKPLS"), response, scoring.clone()).unwrap();
+
+        let guide = rules.parse("R W\nK D").unwrap();
+        // Spock and paper both beat rock, but paper scores more; then Spock draws.
+        assert_eq!(rules.score(&guide).unwrap(), (3 + 6) + (2 + 3));
+        assert!(rules.parse("R X").is_err());
+        assert!(rules.score(&[('Q', 'W')]).is_err());
+
+        let response = Response::Move(symbols("VWXYZ"));
+        assert!(Rules::new(game, symbols("ABCDEFG"), response, scoring).is_err());
+    }
+
+    #[test]
+    fn test_optimizer() {
+        let guide = generator(example!("day2")).unwrap();
+        let opponents = opponents(&guide).unwrap();
+        assert_eq!(best_score(&opponents), 8 + 9 + 7);
+        assert_eq!(worst_score(&opponents), 3 + 1 + 2);
+
+        let plays = plan(&opponents, 15).unwrap();
+        let guide = write_guide(&opponents, &plays, Column::Move);
+        assert_eq!(part1(&guide).unwrap(), 15);
+        let guide = write_guide(&opponents, &plays, Column::Outcome);
+        assert_eq!(part2(&guide).unwrap(), 15);
+
+        let error = plan(&opponents, 25).unwrap_err();
+        assert!(
+            error.to_string().contains("range from 6 to 24"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_plan_many_rounds() {
+        let opponents = (0..100_000)
+            .map(|i| Play::ALL[i * 7 % 3])
+            .collect::<Vec<_>>();
+        let (worst, best) = (worst_score(&opponents), best_score(&opponents));
+        for target in [worst, (worst + best) / 2, best] {
+            let plays = plan(&opponents, target).unwrap();
+            let guide = write_guide(&opponents, &plays, Column::Move);
+            assert_eq!(part1(&guide).unwrap(), target);
+        }
+    }
+
+    #[test]
+    fn test_plan_matches_brute_force() {
+        let opponents = [Play::Rock, Play::Paper, Play::Scissors, Play::Rock];
+        let mut totals = vec![0];
+        for &opponent in &opponents {
+            totals = totals
+                .iter()
+                .flat_map(|total| choices(opponent).map(|(_, score)| total + score))
+                .collect();
+        }
+
+        for target in 0..=40 {
+            match plan(&opponents, target) {
+                Ok(plays) => {
+                    let guide = write_guide(&opponents, &plays, Column::Move);
+                    assert_eq!(part1(&guide).unwrap(), target);
+                }
+                Err(_) => assert!(!totals.contains(&target), "{}", target),
+            }
+        }
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day2")).unwrap();
+        assert_eq!(part1(&input).unwrap(), 15);
+        assert!(part1(&[('A', 'W')]).is_err());
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day2")).unwrap();
+        assert_eq!(part2(&input).unwrap(), 12);
+        assert!(part2(&[('D', 'X')]).is_err());
+    }
 }
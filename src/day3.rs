@@ -1,51 +1,483 @@
-use std::collections::HashSet;
+//! Day 3: Rucksack Reorganization.
+
+use std::{error::Error, fmt, io::BufRead, ops::RangeInclusive};
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
-fn priority(c: char) -> u32 {
-    if c.is_uppercase() {
-        u32::from(c) - 64 + 26
-    } else {
-        u32::from(c) - 96
+use crate::error::{collect_lines, ParseError, ReadError};
+
+/// The priority of an item letter: 1-26 for `a`-`z` and 27-52 for `A`-`Z`.
+pub fn priority(c: char) -> Option<u32> {
+    match c {
+        'a'..='z' => Some(u32::from(c) - u32::from('a') + 1),
+        'A'..='Z' => Some(u32::from(c) - u32::from('A') + 27),
+        _ => None,
+    }
+}
+
+fn parse_sack(input: &str, line: &str) -> Result<String, ParseError> {
+    match line.find(|c| priority(c).is_none()) {
+        Some(i) => Err(ParseError::at(
+            3,
+            input,
+            &line[i..],
+            "an item letter a-z or A-Z",
+        )),
+        None => Ok(line.to_string()),
     }
 }
 
+/// Parses one rucksack per line.
 #[aoc_generator(day3)]
-fn generator(input: &str) -> Vec<String> {
-    input.lines().map(str::to_string).collect()
+pub fn generator(input: &str) -> Result<Vec<String>, ParseError> {
+    input.lines().map(|line| parse_sack(input, line)).collect()
+}
+
+/// Streaming version of [`generator`].
+pub fn read(reader: impl BufRead) -> Result<Vec<String>, ReadError> {
+    collect_lines(reader, |line| parse_sack(line, line))
+}
+
+/// The number of elves in each badge group.
+const GROUP_SIZE: usize = 3;
+
+/// The item with the given priority, the inverse of [`priority`].
+fn item(priority: u32) -> char {
+    match priority {
+        1..=26 => char::from(b'a' + priority as u8 - 1),
+        _ => char::from(b'A' + priority as u8 - 27),
+    }
+}
+
+/// A set of items, stored as one bit per priority.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    /// The set with no items.
+    pub const EMPTY: Self = Self(0);
+    /// Every item from `a` to `Z`.
+    pub const ALL: Self = Self(((1 << 52) - 1) << 1);
+
+    /// Adds `c`, returning false if it is not an item letter.
+    pub fn insert(&mut self, c: char) -> bool {
+        let Some(p) = priority(c) else {
+            return false;
+        };
+        self.0 |= 1 << p;
+        true
+    }
+
+    /// Whether the set holds item `c`.
+    pub fn contains(&self, c: char) -> bool {
+        priority(c).is_some_and(|p| self.0 & 1 << p != 0)
+    }
+
+    /// The items in both sets.
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The items in either set.
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// The number of items in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Whether the set holds no items.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The priority of each item, in increasing order.
+    pub fn priorities(&self) -> impl Iterator<Item = u32> {
+        let mut bits = self.0;
+        std::iter::from_fn(move || {
+            let priority = bits.trailing_zeros();
+            (bits != 0).then(|| {
+                bits &= bits - 1;
+                priority
+            })
+        })
+    }
+
+    /// The items in priority order.
+    pub fn iter(&self) -> impl Iterator<Item = char> {
+        self.priorities().map(item)
+    }
+
+    /// The summed priority of every item in the set.
+    pub fn priority(&self) -> u32 {
+        self.priorities().sum()
+    }
+}
+
+/// Collects the item letters, skipping anything else.
+impl FromIterator<char> for ItemSet {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut set = Self::EMPTY;
+        for c in iter {
+            set.insert(c);
+        }
+        set
+    }
+}
+
+/// A rule broken by a sack or group, with 1-based line numbers.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Problem {
+    /// The sack cannot be split into two equal compartments.
+    OddLength {
+        /// The sack's line.
+        line: usize,
+        /// The number of items in the sack.
+        length: usize,
+    },
+    /// The sack holds something other than a letter.
+    NotAnItem {
+        /// The sack's line.
+        line: usize,
+        /// The 1-based column of the stray character.
+        column: usize,
+        /// The stray character.
+        item: char,
+    },
+    /// The compartments share something other than exactly one item.
+    SharedItems {
+        /// The sack's line.
+        line: usize,
+        /// Every item found in both compartments.
+        items: Vec<char>,
+    },
+    /// The group has something other than exactly one badge.
+    Badges {
+        /// The lines of the group's sacks.
+        lines: RangeInclusive<usize>,
+        /// Every item carried by the whole group.
+        badges: Vec<char>,
+    },
+    /// The input ran out part way through the last group.
+    IncompleteGroup {
+        /// The lines of the sacks in the group so far.
+        lines: RangeInclusive<usize>,
+        /// The number of sacks a full group holds.
+        size: usize,
+    },
+}
+
+fn items(items: &[char]) -> String {
+    match items {
+        [] => "nothing".to_string(),
+        _ => items
+            .iter()
+            .map(|c| format!("`{}`", c))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::OddLength { line, length } => write!(
+                f,
+                "line {}: a sack of {} items cannot be split into two equal compartments",
+                line, length
+            ),
+            Problem::NotAnItem { line, column, item } => write!(
+                f,
+                "line {}, column {}: {:?} is not an item letter a-z or A-Z",
+                line, column, item
+            ),
+            Problem::SharedItems {
+                line,
+                items: shared,
+            } => write!(
+                f,
+                "line {}: the compartments should share one item, but share {}",
+                line,
+                items(shared)
+            ),
+            Problem::Badges { lines, badges } => write!(
+                f,
+                "lines {}-{}: the group should share one badge, but shares {}",
+                lines.start(),
+                lines.end(),
+                items(badges)
+            ),
+            Problem::IncompleteGroup { lines, size } => write!(
+                f,
+                "lines {}-{}: the last group has {} sacks instead of {}",
+                lines.start(),
+                lines.end(),
+                lines.end() - lines.start() + 1,
+                size
+            ),
+        }
+    }
+}
+
+/// Every problem found by [`check`], in input order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckError(pub Vec<Problem>);
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for problem in &self.0 {
+            writeln!(f, "error: {}", problem)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for CheckError {}
+
+/// A group of sacks and the one badge they share.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Group {
+    /// The 1-based lines holding the group's sacks.
+    pub lines: RangeInclusive<usize>,
+    /// The item every sack in the group carries.
+    pub badge: char,
+}
+
+/// Checks that every sack holds only items, splits evenly into compartments
+/// sharing exactly one item, and that each group of `group_size` sacks shares
+/// exactly one badge. Returns each group's badge, or every rule broken.
+///
+/// # Panics
+///
+/// If `group_size` is 0.
+pub fn check(input: &str, group_size: usize) -> Result<Vec<Group>, CheckError> {
+    let sacks = input.lines().collect::<Vec<_>>();
+    let mut problems = vec![];
+    let mut groups = vec![];
+
+    for (i, group) in sacks.chunks(group_size).enumerate() {
+        let first = i * group_size + 1;
+        let lines = first..=first + group.len() - 1;
+
+        for (line, sack) in lines.clone().zip(group) {
+            let invalid = sack
+                .chars()
+                .enumerate()
+                .filter(|&(_, c)| priority(c).is_none())
+                .map(|(i, item)| Problem::NotAnItem {
+                    line,
+                    column: i + 1,
+                    item,
+                })
+                .collect::<Vec<_>>();
+            let sound = invalid.is_empty();
+            problems.extend(invalid);
+
+            let length = sack.chars().count();
+            if length % 2 != 0 {
+                problems.push(Problem::OddLength { line, length });
+            } else if sound {
+                let (left, right) = sack.split_at(sack.len() / 2);
+                let shared = left
+                    .chars()
+                    .collect::<ItemSet>()
+                    .intersection(right.chars().collect());
+                if shared.len() != 1 {
+                    problems.push(Problem::SharedItems {
+                        line,
+                        items: shared.iter().collect(),
+                    });
+                }
+            }
+        }
+
+        if group.len() < group_size {
+            problems.push(Problem::IncompleteGroup {
+                lines,
+                size: group_size,
+            });
+            continue;
+        }
+
+        let badges = group
+            .iter()
+            .map(|sack| sack.chars().collect::<ItemSet>())
+            .fold(ItemSet::ALL, ItemSet::intersection);
+        match badges.iter().collect::<Vec<_>>()[..] {
+            [badge] => groups.push(Group { lines, badge }),
+            ref badges => problems.push(Problem::Badges {
+                lines,
+                badges: badges.to_vec(),
+            }),
+        }
+    }
+
+    match problems.is_empty() {
+        true => Ok(groups),
+        false => Err(CheckError(problems)),
+    }
 }
 
+/// The summed priority of the item shared by both compartments of each sack.
 #[aoc(day3, part1)]
-fn part1(input: &[String]) -> u32 {
+pub fn part1(input: &[String]) -> u32 {
     input
         .iter()
         .map(|sack| {
-            let chars = sack.chars().collect::<Vec<_>>();
-            let (left, right) = chars.split_at(sack.len() / 2);
-
-            let left = left.iter().collect::<HashSet<_>>();
-            let right = right.iter().collect::<HashSet<_>>();
-            left.intersection(&right)
-                .map(|&c| priority(*c))
-                .sum::<u32>()
+            let (left, right) = sack.split_at(sack.len() / 2);
+            let left = left.chars().collect::<ItemSet>();
+            let right = right.chars().collect::<ItemSet>();
+            left.intersection(right).priority()
         })
         .sum()
 }
 
-#[aoc(day3, part2)]
-fn part2(input: &[String]) -> u32 {
+/// The summed priority of the badge shared by each group of `group_size`
+/// consecutive sacks.
+///
+/// # Panics
+///
+/// If `group_size` is 0.
+pub fn badge_priorities(input: &[String], group_size: usize) -> u32 {
     input
-        .chunks(3)
+        .chunks(group_size)
         .map(|sacks| {
-            let badges = sacks
-                .iter()
-                .map(|sack| sack.chars().collect::<HashSet<_>>())
-                .reduce(|acc, item| &acc & &item);
-
-            badges
+            sacks
                 .iter()
-                .map(|chars| chars.iter().map(|&c| priority(c)).sum::<u32>())
-                .sum::<u32>()
+                .map(|sack| sack.chars().collect::<ItemSet>())
+                .fold(ItemSet::ALL, ItemSet::intersection)
+                .priority()
         })
         .sum()
 }
+
+/// The summed priority of each three-elf group's badge.
+#[aoc(day3, part2)]
+pub fn part2(input: &[String]) -> u32 {
+    badge_priorities(input, GROUP_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day3")).unwrap();
+        assert_eq!(input.len(), 6);
+        assert_eq!(input[0], "vJrwpWtwJgWrhcsFMMfFFhFp");
+    }
+
+    #[test]
+    fn test_priority() {
+        assert_eq!(priority('a'), Some(1));
+        assert_eq!(priority('z'), Some(26));
+        assert_eq!(priority('A'), Some(27));
+        assert_eq!(priority('Z'), Some(52));
+        assert_eq!(priority('1'), None);
+        assert_eq!(priority('é'), None);
+    }
+
+    #[test]
+    fn test_item_set() {
+        let a = "vJrwpWtwJgWrhcsFMMfFFhFp".chars().collect::<ItemSet>();
+        let b = "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL"
+            .chars()
+            .collect::<ItemSet>();
+        let shared = a.intersection(b);
+        assert_eq!(shared.iter().collect::<String>(), "frsFM");
+        assert_eq!(shared.priority(), 6 + 18 + 19 + 32 + 39);
+        assert!(a.contains('v') && !a.contains('Z') && !a.contains('1'));
+        assert_eq!(a.union(b).len(), a.len() + b.len() - shared.len());
+        assert_eq!(ItemSet::ALL.len(), 52);
+        assert_eq!(
+            ItemSet::ALL.iter().map(priority).collect::<Vec<_>>(),
+            (1..=52).map(Some).collect::<Vec<_>>()
+        );
+        assert!(ItemSet::EMPTY.is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let groups = check(example!("day3"), 3).unwrap();
+        assert_eq!(
+            groups,
+            [
+                Group {
+                    lines: 1..=3,
+                    badge: 'r'
+                },
+                Group {
+                    lines: 4..=6,
+                    badge: 'Z'
+                }
+            ]
+        );
+
+        let error = check("abcA\naBca\nabc\nxy1y\nabab", 2).unwrap_err();
+        assert_eq!(
+            error.0,
+            [
+                Problem::SharedItems {
+                    line: 1,
+                    items: vec![]
+                },
+                Problem::Badges {
+                    lines: 1..=2,
+                    badges: vec!['a', 'c']
+                },
+                Problem::OddLength { line: 3, length: 3 },
+                Problem::NotAnItem {
+                    line: 4,
+                    column: 3,
+                    item: '1'
+                },
+                Problem::Badges {
+                    lines: 3..=4,
+                    badges: vec![]
+                },
+                Problem::SharedItems {
+                    line: 5,
+                    items: vec!['a', 'b']
+                },
+                Problem::IncompleteGroup {
+                    lines: 5..=5,
+                    size: 2
+                },
+            ]
+        );
+        assert_eq!(
+            error.0[3].to_string(),
+            "line 4, column 3: '1' is not an item letter a-z or A-Z"
+        );
+        assert_eq!(
+            error.0[1].to_string(),
+            "lines 1-2: the group should share one badge, but shares `a`, `c`"
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let input = example!("day3");
+        assert_eq!(read(input.as_bytes()).unwrap(), generator(input).unwrap());
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day3")).unwrap();
+        assert_eq!(part1(&input), 157);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day3")).unwrap();
+        assert_eq!(part2(&input), 70);
+
+        let sacks = ["ab", "bc", "bd", "ad"].map(String::from);
+        assert_eq!(badge_priorities(&sacks, 2), 2 + 4);
+        assert_eq!(badge_priorities(&sacks, 3), 2 + 1 + 4);
+    }
+}
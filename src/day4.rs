@@ -1,44 +1,256 @@
-use std::collections::HashSet;
+//! Day 4: Camp Cleanup.
+
+use std::io::BufRead;
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::ContextCompat;
+use nom::{
+    character::complete::{char, u32},
+    combinator::map_opt,
+    sequence::separated_pair,
+    IResult,
+};
+
+use crate::{
+    error::{collect_lines, parse_complete, ParseError, ReadError},
+    interval::{Interval, IntervalSet},
+};
 
-type Assignments = HashSet<u32>;
+/// The range of section IDs assigned to one elf.
+pub type Assignments = Interval;
 
-fn parse_assignments(part: &str) -> Option<Assignments> {
-    let mut range_parts = part.split('-');
-    let start = range_parts.next().and_then(|n| n.parse().ok())?;
-    let end = range_parts.next().and_then(|n| n.parse().ok())?;
-    let set = (start..=end).collect();
-    Some(set)
+fn parse_assignments(input: &str) -> IResult<&str, Assignments> {
+    map_opt(separated_pair(u32, char('-'), u32), |(start, end)| {
+        Interval::new(start, end)
+    })(input)
 }
 
+fn parse_pair(input: &str, line: &str) -> Result<(Assignments, Assignments), ParseError> {
+    let pair = separated_pair(parse_assignments, char(','), parse_assignments);
+    parse_complete(
+        4,
+        input,
+        line,
+        pair,
+        "a pair of ascending ranges like `2-4,6-8`",
+    )
+}
+
+/// Parses each pair of elves' section assignments.
 #[aoc_generator(day4)]
-fn generator(input: &str) -> eyre::Result<Vec<(Assignments, Assignments)>> {
-    input
-        .lines()
-        .map(|line| {
-            let mut parts = line.split(',');
-            let left = parts.next().and_then(parse_assignments)?;
-            let right = parts.next().and_then(parse_assignments)?;
-            Some((left, right))
-        })
-        .collect::<Option<Vec<_>>>()
-        .context("unable to parse input")
+pub fn generator(input: &str) -> Result<Vec<(Assignments, Assignments)>, ParseError> {
+    input.lines().map(|line| parse_pair(input, line)).collect()
+}
+
+/// Streaming version of [`generator`].
+pub fn read(reader: impl BufRead) -> Result<Vec<(Assignments, Assignments)>, ReadError> {
+    collect_lines(reader, |line| parse_pair(line, line))
+}
+
+/// Section coverage across every elf in the crew, not just within pairs.
+/// Elves are numbered in input order, so pair `i` is elves `2 * i` and
+/// `2 * i + 1`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Coverage {
+    elves: Vec<Interval>,
+    /// How many elves cover each section, as maximal runs of equal depth from
+    /// the lowest assigned section to the highest.
+    depths: Vec<(Interval, usize)>,
+}
+
+impl Coverage {
+    /// Sweeps over every elf's assignment in `input`.
+    pub fn new(input: &[(Assignments, Assignments)]) -> Self {
+        let elves = input
+            .iter()
+            .flat_map(|&(left, right)| [left, right])
+            .collect::<Vec<_>>();
+
+        // Each elf raises the depth at its start and lowers it just past its end.
+        let mut events = elves
+            .iter()
+            .flat_map(|elf| [(u64::from(elf.start()), 1), (u64::from(elf.end()) + 1, -1)])
+            .collect::<Vec<(u64, i64)>>();
+        events.sort_unstable();
+
+        let mut depths = Vec::<(Interval, usize)>::new();
+        let mut depth = 0;
+        for (i, &(position, delta)) in events.iter().enumerate() {
+            depth += delta;
+            let Some(&(next, _)) = events.get(i + 1).filter(|(next, _)| *next > position) else {
+                continue;
+            };
+
+            let run = Interval::new(position as u32, (next - 1) as u32);
+            match (depths.last_mut(), run) {
+                (Some((last, d)), Some(run)) if *d == depth as usize => {
+                    *last = Interval::new(last.start(), run.end()).unwrap_or(run);
+                }
+                (_, Some(run)) => depths.push((run, depth as usize)),
+                _ => {}
+            }
+        }
+
+        Self { elves, depths }
+    }
+
+    /// The sections nobody covers between the lowest and highest assigned
+    /// sections.
+    pub fn uncovered(&self) -> IntervalSet {
+        self.depths
+            .iter()
+            .filter(|&&(_, depth)| depth == 0)
+            .map(|&(run, _)| run)
+            .collect()
+    }
+
+    /// The sections covered by more than `k` elves.
+    pub fn covered_by_more_than(&self, k: usize) -> IntervalSet {
+        self.depths
+            .iter()
+            .filter(|&&(_, depth)| depth > k)
+            .map(|&(run, _)| run)
+            .collect()
+    }
+
+    /// The most elves covering any one section.
+    pub fn max_overlap(&self) -> usize {
+        self.depths
+            .iter()
+            .map(|&(_, depth)| depth)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Every pair of elves whose assignments overlap, as sorted `(a, b)` with
+    /// `a < b`. Sweeps the elves by start section, keeping those whose
+    /// assignments are still open.
+    pub fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+        let mut order = (0..self.elves.len()).collect::<Vec<_>>();
+        order.sort_unstable_by_key(|&i| self.elves[i]);
+
+        let mut open = Vec::<usize>::new();
+        let mut pairs = vec![];
+        for i in order {
+            let start = self.elves[i].start();
+            open.retain(|&j| self.elves[j].end() >= start);
+            pairs.extend(open.iter().map(|&j| (i.min(j), i.max(j))));
+            open.push(i);
+        }
+
+        pairs.sort_unstable();
+        pairs
+    }
 }
 
+/// The number of pairs where one assignment fully contains the other.
 #[aoc(day4, part1)]
-fn part1(input: &[(Assignments, Assignments)]) -> usize {
+pub fn part1(input: &[(Assignments, Assignments)]) -> usize {
     input
         .iter()
-        .filter(|(left, right)| left.is_subset(right) || right.is_subset(left))
+        .filter(|(left, right)| left.covers(right) || right.covers(left))
         .count()
 }
 
+/// The number of pairs whose assignments overlap.
 #[aoc(day4, part2)]
-fn part2(input: &[(Assignments, Assignments)]) -> usize {
+pub fn part2(input: &[(Assignments, Assignments)]) -> usize {
     input
         .iter()
-        .filter(|(left, right)| !left.is_disjoint(right))
+        .filter(|(left, right)| left.overlaps(right))
         .count()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day4")).unwrap();
+        assert_eq!(input.len(), 6);
+        assert_eq!(input[0].0, Interval::new(2, 4).unwrap());
+        assert_eq!(input[0].1, Interval::new(6, 8).unwrap());
+
+        let input = generator("1-4000000000,4000000000-4000000000").unwrap();
+        assert_eq!(part1(&input), 1);
+        assert_eq!(input[0].0.len(), 4000000000);
+
+        let error = generator("2-4,8-6").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 5));
+    }
+
+    #[test]
+    fn test_read() {
+        let input = example!("day4");
+        assert_eq!(read(input.as_bytes()).unwrap(), generator(input).unwrap());
+    }
+
+    #[test]
+    fn test_coverage() {
+        let interval = |start, end| Interval::new(start, end).unwrap();
+        let input = [
+            (interval(1, 2), interval(5, 6)),
+            (interval(6, 9), interval(2, 2)),
+        ];
+        let coverage = Coverage::new(&input);
+        let runs = |set: IntervalSet| set.iter().collect::<Vec<_>>();
+        assert_eq!(runs(coverage.uncovered()), [interval(3, 4)]);
+        assert_eq!(
+            runs(coverage.covered_by_more_than(1)),
+            [interval(2, 2), interval(6, 6)]
+        );
+        assert_eq!(coverage.max_overlap(), 2);
+        assert_eq!(coverage.overlapping_pairs(), [(0, 3), (1, 2)]);
+        assert_eq!(Coverage::new(&[]).max_overlap(), 0);
+
+        let input = [(interval(4, u32::MAX), interval(u32::MAX, u32::MAX))];
+        let coverage = Coverage::new(&input);
+        assert_eq!(
+            runs(coverage.covered_by_more_than(1)),
+            [interval(u32::MAX, u32::MAX)]
+        );
+        assert!(coverage.uncovered().is_empty());
+    }
+
+    #[test]
+    fn test_coverage_matches_brute_force() {
+        let input = generator(example!("day4")).unwrap();
+        let coverage = Coverage::new(&input);
+        let elves = input
+            .iter()
+            .flat_map(|&(left, right)| [left, right])
+            .collect::<Vec<_>>();
+        let depth = |section| elves.iter().filter(|elf| elf.contains(section)).count();
+
+        assert!(coverage.uncovered().is_empty());
+        assert_eq!(coverage.max_overlap(), (0..=10).map(depth).max().unwrap());
+        for k in 0..5 {
+            let deep = coverage.covered_by_more_than(k);
+            for section in 0..=10 {
+                assert_eq!(deep.contains(section), depth(section) > k);
+            }
+        }
+
+        let mut pairs = vec![];
+        for a in 0..elves.len() {
+            for b in a + 1..elves.len() {
+                if elves[a].overlaps(&elves[b]) {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        assert_eq!(coverage.overlapping_pairs(), pairs);
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day4")).unwrap();
+        assert_eq!(part1(&input), 2);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day4")).unwrap();
+        assert_eq!(part2(&input), 4);
+    }
+}
@@ -1,84 +1,159 @@
-use std::collections::{BTreeMap, HashMap};
+//! Day 5: Supply Stacks.
+
+use std::{collections::BTreeMap, fmt};
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::ContextCompat;
+use eyre::{ensure, ContextCompat, WrapErr};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{u32, u64},
+    combinator::map,
+    sequence::tuple,
+};
+
+use crate::error::{parse_complete, ParseError};
 
-struct Instruction {
-    count: usize,
-    source: u32,
-    destination: u32,
+/// Moves `count` crates from the `source` stack to the `destination` stack.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Instruction {
+    /// The number of crates to move.
+    pub count: usize,
+    /// The label of the stack to take them from.
+    pub source: u32,
+    /// The label of the stack to put them on.
+    pub destination: u32,
 }
 
-struct Input {
-    stacks: BTreeMap<u32, Vec<char>>,
-    instructions: Vec<Instruction>,
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.count, self.source, self.destination
+        )
+    }
 }
 
-fn parse_stacks(input: &str) -> eyre::Result<BTreeMap<u32, Vec<char>>> {
-    let mut lines = input.lines().rev();
-    let labels = lines
-        .next()
-        .map(|line| {
-            line.chars()
-                .enumerate()
-                .filter_map(|(i, c)| c.to_digit(10).map(|n| (i, n)))
-                .collect::<HashMap<usize, u32>>()
+/// Stacks of crates, bottom crate first, keyed by label.
+pub type Stacks = BTreeMap<u32, Vec<char>>;
+
+/// The starting stacks and the rearrangement procedure.
+#[derive(Clone, Debug)]
+pub struct Input {
+    /// The stacks before any crates are moved.
+    pub stacks: Stacks,
+    /// The moves, in order.
+    pub instructions: Vec<Instruction>,
+}
+
+/// Splits `line` on spaces, giving each non-empty token and its byte offset.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split(' ')
+        .scan(0, |offset, token| {
+            let start = *offset;
+            *offset += token.len() + 1;
+            Some((start, token))
         })
-        .context("parsing labels")?;
+        .filter(|(_, token)| !token.is_empty())
+}
 
-    let stacks = lines.fold(BTreeMap::<u32, Vec<char>>::new(), |mut acc, line| {
-        for (i, c) in line.chars().enumerate() {
-            if !c.is_ascii_alphabetic() {
-                continue;
-            }
+/// Parses the drawing, matching each crate `[A]` to the label whose columns
+/// it overlaps, so labels may be any number of digits wide.
+fn parse_stacks<'a>(input: &'a str, drawing: &'a str) -> Result<Stacks, ParseError> {
+    let error = |position: &str, expected: &str| ParseError::at(5, input, position, expected);
+    let mut lines = drawing.lines().rev();
+    let label_row = lines.next().unwrap_or(drawing);
 
-            match labels.get(&i) {
-                Some(&s) => {
-                    acc.entry(s).or_default().push(c);
-                }
-                None => continue,
-            }
+    let mut labels = vec![];
+    let mut stacks = BTreeMap::new();
+    for (offset, token) in tokens(label_row) {
+        let label = token
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| token.parse::<u32>().ok())
+            .flatten()
+            .ok_or_else(|| error(&label_row[offset..], "a stack label"))?;
+        if stacks.insert(label, vec![]).is_some() {
+            return Err(error(
+                &label_row[offset..],
+                "a label not used by another stack",
+            ));
         }
+        labels.push((offset..offset + token.len(), label));
+    }
+    if labels.is_empty() {
+        return Err(error(label_row, "a row of stack labels"));
+    }
 
-        acc
-    });
+    for (height, line) in lines.enumerate() {
+        for (offset, token) in tokens(line) {
+            let position = &line[offset..];
+            let mut chars = token.chars();
+            let c = match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                (Some('['), Some(c), Some(']'), None) if c.is_ascii_graphic() => c,
+                _ => return Err(error(position, "a crate like `[A]`")),
+            };
+
+            let span = offset..offset + token.len();
+            let mut above = labels
+                .iter()
+                .filter(|(columns, _)| columns.start < span.end && span.start < columns.end);
+            let label = match (above.next(), above.next()) {
+                (Some(&(_, label)), None) => label,
+                _ => return Err(error(position, "a crate in line with one stack label")),
+            };
+
+            let stack = stacks.entry(label).or_default();
+            if stack.len() != height {
+                return Err(error(position, "a crate resting on another crate"));
+            }
+            stack.push(c);
+        }
+    }
 
     Ok(stacks)
 }
 
-fn parse_instructions(input: &str) -> eyre::Result<Vec<Instruction>> {
-    input
+fn parse_instructions<'a>(
+    input: &'a str,
+    procedure: &'a str,
+) -> Result<Vec<Instruction>, ParseError> {
+    procedure
         .lines()
         .map(|line| {
-            let mut parts = line.split_whitespace();
-            let _ = parts.next()?;
-            let count = parts.next().and_then(|m| m.parse().ok())?;
-            let _ = parts.next()?;
-            let source = parts.next().and_then(|m| m.parse().ok())?;
-            let _ = parts.next()?;
-            let destination = parts.next().and_then(|m| m.parse().ok())?;
-            Some(Instruction {
-                count,
-                source,
-                destination,
-            })
+            let instruction = map(
+                tuple((tag("move "), u64, tag(" from "), u32, tag(" to "), u32)),
+                |(_, count, _, source, _, destination)| Instruction {
+                    count: count as usize,
+                    source,
+                    destination,
+                },
+            );
+            parse_complete(
+                5,
+                input,
+                line,
+                instruction,
+                "an instruction like `move 1 from 2 to 1`",
+            )
         })
-        .collect::<Option<Vec<_>>>()
-        .context("parsing instructions")
+        .collect()
 }
 
+/// Parses the starting stack drawing and the rearrangement procedure.
 #[aoc_generator(day5)]
-fn generator(input: &str) -> eyre::Result<Input> {
-    let mut parts = input.split("\n\n");
-
-    let stacks = parts
-        .next()
-        .context("reading stacks")
-        .and_then(parse_stacks)?;
-    let instructions = parts
-        .next()
-        .context("reading instructions")
-        .and_then(parse_instructions)?;
+pub fn generator(input: &str) -> Result<Input, ParseError> {
+    let (drawing, procedure) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::new(
+            5,
+            input,
+            input.len(),
+            "a blank line before the instructions",
+        )
+    })?;
+
+    let stacks = parse_stacks(input, drawing)?;
+    let instructions = parse_instructions(input, procedure)?;
 
     Ok(Input {
         stacks,
@@ -86,59 +161,463 @@ fn generator(input: &str) -> eyre::Result<Input> {
     })
 }
 
+/// Whether a drawing pads every row to its full width, as the puzzle does, or
+/// trims the spaces from the end of each row.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Padding {
+    /// Every row is as wide as the label row, as in the puzzle.
+    #[default]
+    Full,
+    /// No row ends in a space.
+    Trimmed,
+}
+
+impl Padding {
+    /// The padding used by `drawing`: [`Padding::Full`] if any of its lines
+    /// ends with a space, otherwise [`Padding::Trimmed`].
+    pub fn of(drawing: &str) -> Self {
+        if drawing.lines().any(|line| line.ends_with(' ')) {
+            Padding::Full
+        } else {
+            Padding::Trimmed
+        }
+    }
+}
+
+/// Draws `stacks` as the puzzle does: a `[A]` cell per crate, one column per
+/// stack separated by spaces, and a row of labels. Drawings in that layout,
+/// rendered with their own [`Padding::of`], come back byte for byte after
+/// [`generator`] parses them.
+pub fn render(stacks: &Stacks, padding: Padding) -> String {
+    let width = stacks
+        .keys()
+        .map(|label| label.to_string().len() + 1)
+        .max()
+        .unwrap_or_default()
+        .max(3);
+    let height = stacks.values().map(Vec::len).max().unwrap_or_default();
+
+    let mut rows = (0..height)
+        .rev()
+        .map(|y| {
+            stacks
+                .values()
+                .map(|stack| match stack.get(y) {
+                    Some(c) => format!("{:<width$}", format!("[{}]", c), width = width),
+                    None => " ".repeat(width),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>();
+    rows.push(
+        stacks
+            .keys()
+            .map(|label| format!(" {:<width$}", label, width = width - 1))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    if padding == Padding::Trimmed {
+        for row in &mut rows {
+            row.truncate(row.trim_end().len());
+        }
+    }
+    rows.join("\n")
+}
+
+/// Removes the top `count` crates from stack `source`, bottom crate first.
+fn lift(stacks: &mut Stacks, source: u32, count: usize) -> eyre::Result<Vec<char>> {
+    let stack = stacks
+        .get_mut(&source)
+        .with_context(|| format!("there is no stack {}", source))?;
+    let start = stack.len().checked_sub(count).with_context(|| {
+        format!(
+            "cannot move {} crates from stack {}, which only holds {}",
+            count,
+            source,
+            stack.len()
+        )
+    })?;
+
+    Ok(stack.drain(start..).collect())
+}
+
+/// A crane model, which decides how a lifted group of crates lands.
+pub trait Crane {
+    /// Reorders `crates`, lifted from the top of a stack bottom crate first,
+    /// into the order they land on the destination, bottom crate first.
+    fn arrange(&self, crates: &mut Vec<char>);
+
+    /// The inverse of [`arrange`](Crane::arrange): reorders crates as landed
+    /// back into the order they were lifted.
+    fn unarrange(&self, crates: &mut Vec<char>);
+
+    /// Carries out one instruction, leaving `stacks` untouched if it fails.
+    fn apply(&self, stacks: &mut Stacks, instruction: &Instruction) -> eyre::Result<()> {
+        ensure!(
+            stacks.contains_key(&instruction.destination),
+            "there is no stack {}",
+            instruction.destination
+        );
+        let mut moving = lift(stacks, instruction.source, instruction.count)?;
+        self.arrange(&mut moving);
+
+        if let Some(destination) = stacks.get_mut(&instruction.destination) {
+            destination.extend(moving);
+        }
+        Ok(())
+    }
+
+    /// Undoes one instruction, returning the moved crates to their source and
+    /// leaving `stacks` untouched if it fails.
+    fn undo(&self, stacks: &mut Stacks, instruction: &Instruction) -> eyre::Result<()> {
+        ensure!(
+            stacks.contains_key(&instruction.source),
+            "there is no stack {}",
+            instruction.source
+        );
+        let mut moved = lift(stacks, instruction.destination, instruction.count)?;
+        self.unarrange(&mut moved);
+
+        if let Some(source) = stacks.get_mut(&instruction.source) {
+            source.extend(moved);
+        }
+        Ok(())
+    }
+}
+
+/// Moves crates one at a time, reversing each group.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn arrange(&self, crates: &mut Vec<char>) {
+        crates.reverse();
+    }
+
+    fn unarrange(&self, crates: &mut Vec<char>) {
+        crates.reverse();
+    }
+}
+
+/// Moves each group at once, keeping its order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn arrange(&self, _: &mut Vec<char>) {}
+
+    fn unarrange(&self, _: &mut Vec<char>) {}
+}
+
+/// Moves at most `capacity` crates at once, splitting larger groups into
+/// lifts taken from the top.
+#[derive(Clone, Copy, Debug)]
+pub struct CapacityLimited {
+    capacity: usize,
+}
+
+impl CapacityLimited {
+    /// A crane lifting at most `capacity` crates at once, which must be at
+    /// least one.
+    pub fn new(capacity: usize) -> eyre::Result<Self> {
+        ensure!(capacity > 0, "a crane must lift at least one crate");
+        Ok(Self { capacity })
+    }
+}
+
+impl Crane for CapacityLimited {
+    fn arrange(&self, crates: &mut Vec<char>) {
+        *crates = crates.rchunks(self.capacity).flatten().copied().collect();
+    }
+
+    fn unarrange(&self, crates: &mut Vec<char>) {
+        *crates = crates
+            .chunks(self.capacity)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+    }
+}
+
+/// Runs every instruction with `crane`, calling `step` with each instruction
+/// and the stacks after it.
+pub fn simulate(
+    input: &Input,
+    crane: &dyn Crane,
+    mut step: impl FnMut(&Instruction, &Stacks),
+) -> eyre::Result<Stacks> {
+    let mut stacks = input.stacks.clone();
+    for instruction in &input.instructions {
+        crane.apply(&mut stacks, instruction)?;
+        step(instruction, &stacks);
+    }
+
+    Ok(stacks)
+}
+
+/// The stacks after each instruction, for debugging.
+pub fn trace(input: &Input, crane: &dyn Crane) -> eyre::Result<Vec<Stacks>> {
+    let mut states = vec![];
+    simulate(input, crane, |_, stacks| states.push(stacks.clone()))?;
+    Ok(states)
+}
+
+/// Checks that `instructions` can be carried out with `crane` from the
+/// claimed `start`, naming the first that cannot. Returns the final stacks.
+pub fn validate(
+    start: &Stacks,
+    instructions: &[Instruction],
+    crane: &dyn Crane,
+) -> eyre::Result<Stacks> {
+    let mut stacks = start.clone();
+    for (i, instruction) in instructions.iter().enumerate() {
+        crane
+            .apply(&mut stacks, instruction)
+            .wrap_err_with(|| format!("instruction {} (`{}`) is invalid", i + 1, instruction))?;
+    }
+
+    Ok(stacks)
+}
+
+/// Recovers the starting stacks from the `end` state by undoing each of
+/// `instructions` in reverse, naming the first that cannot be undone.
+pub fn unsimulate(
+    end: &Stacks,
+    instructions: &[Instruction],
+    crane: &dyn Crane,
+) -> eyre::Result<Stacks> {
+    let mut stacks = end.clone();
+    for (i, instruction) in instructions.iter().enumerate().rev() {
+        crane.undo(&mut stacks, instruction).wrap_err_with(|| {
+            format!("instruction {} (`{}`) cannot be undone", i + 1, instruction)
+        })?;
+    }
+
+    Ok(stacks)
+}
+
+/// The crate on top of each stack, in label order.
+pub fn tops(stacks: &Stacks) -> String {
+    stacks.values().filter_map(|stack| stack.last()).collect()
+}
+
+/// The top crates after moving them one at a time.
 #[aoc(day5, part1)]
-fn part1(input: &Input) -> eyre::Result<String> {
-    let stacks = input.instructions.iter().try_fold(
-        input.stacks.clone(),
-        |mut stacks,
-         &Instruction {
-             count,
-             source,
-             destination,
-         }| {
-            let source_stack = stacks.get_mut(&source).context("getting source")?;
-            let mut moving = source_stack
-                .drain((source_stack.len() - count)..)
-                .collect::<Vec<_>>();
-            moving.reverse();
-
-            let destination_stack = stacks
-                .get_mut(&destination)
-                .context("getting destination")?;
-            destination_stack.extend_from_slice(&moving);
-
-            Ok::<_, eyre::Report>(stacks)
-        },
-    )?;
-
-    let answer = stacks.values().filter_map(|stack| stack.last()).collect();
-    Ok(answer)
+pub fn part1(input: &Input) -> eyre::Result<String> {
+    Ok(tops(&simulate(input, &CrateMover9000, |_, _| {})?))
 }
 
+/// The top crates after moving each group at once.
 #[aoc(day5, part2)]
-fn part2(input: &Input) -> eyre::Result<String> {
-    let stacks = input.instructions.iter().try_fold(
-        input.stacks.clone(),
-        |mut stacks,
-         &Instruction {
-             count,
-             source,
-             destination,
-         }| {
-            let source_stack = stacks.get_mut(&source).context("getting source")?;
-            let moving = source_stack
-                .drain((source_stack.len() - count)..)
-                .collect::<Vec<_>>();
-
-            let destination_stack = stacks
-                .get_mut(&destination)
-                .context("getting destination")?;
-            destination_stack.extend_from_slice(&moving);
-
-            Ok::<_, eyre::Report>(stacks)
-        },
-    )?;
-
-    let answer = stacks.values().filter_map(|stack| stack.last()).collect();
-    Ok(answer)
+pub fn part2(input: &Input) -> eyre::Result<String> {
+    Ok(tops(&simulate(input, &CrateMover9001, |_, _| {})?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day5")).unwrap();
+        assert_eq!(input.stacks[&1], vec!['Z', 'N']);
+        assert_eq!(input.stacks[&2], vec!['M', 'C', 'D']);
+        assert_eq!(input.stacks[&3], vec!['P']);
+        assert_eq!(input.instructions.len(), 4);
+    }
+
+    #[test]
+    fn test_wide_drawing() {
+        let labels = (1..=12).map(|n| format!(" {:<2} ", n)).collect::<Vec<_>>();
+        let drawing = format!(
+            "{}\n{}\n{}\n\nmove 2 from 11 to 12",
+            "                                        [K]",
+            "[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [L] [M]",
+            labels.join("").trim_end(),
+        );
+        let input = generator(&drawing).unwrap();
+        assert_eq!(input.stacks.len(), 12);
+        assert_eq!(input.stacks[&9], vec!['I']);
+        assert_eq!(input.stacks[&11], vec!['L', 'K']);
+        assert_eq!(part1(&input).unwrap(), "ABCDEFGHIJL");
+
+        let input = generator("[A] [B]\n  7 100\n\nmove 1 from 7 to 100").unwrap();
+        assert_eq!(input.stacks.keys().collect::<Vec<_>>(), [&7, &100]);
+        assert_eq!(part2(&input).unwrap(), "A");
+    }
+
+    #[test]
+    fn test_render() {
+        let input = example!("day5");
+        let (drawing, _) = input.split_once("\n\n").unwrap();
+        let parsed = generator(input).unwrap();
+        assert_eq!(Padding::of(drawing), Padding::Full);
+        assert_eq!(render(&parsed.stacks, Padding::Full), drawing);
+
+        let trimmed = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3";
+        assert_eq!(Padding::of(trimmed), Padding::Trimmed);
+        let stacks = parse_stacks(trimmed, trimmed).unwrap();
+        assert_eq!(stacks, parsed.stacks);
+        assert_eq!(render(&stacks, Padding::of(trimmed)), trimmed);
+
+        for state in trace(&parsed, &CrateMover9000).unwrap() {
+            for padding in [Padding::Full, Padding::Trimmed] {
+                let drawing = render(&state, padding);
+                assert_eq!(parse_stacks(&drawing, &drawing).unwrap(), state);
+            }
+        }
+
+        let stacks = Stacks::from([(9, vec!['A']), (10, vec![]), (100, vec!['B', 'C'])]);
+        let drawing = "          [C] \n[A]       [B] \n 9    10   100";
+        assert_eq!(render(&stacks, Padding::Full), drawing);
+        assert_eq!(parse_stacks(drawing, drawing).unwrap(), stacks);
+    }
+
+    #[test]
+    fn test_drawing_errors() {
+        let error = |input| {
+            let error = generator(input).unwrap_err();
+            (error.line, error.column, error.expected)
+        };
+        assert_eq!(
+            error("  [A]\n 1   2 \n\nmove 1 from 1 to 2"),
+            (1, 3, "a crate in line with one stack label".to_string())
+        );
+        assert_eq!(error("[A]\n 1   x \n\nmove 1 from 1 to 2").1, 6);
+        assert_eq!(
+            error("[A]\n 1   1 \n\nmove 1 from 1 to 2").2,
+            "a label not used by another stack"
+        );
+        assert_eq!(
+            error("[AB]\n 1   2 \n\nmove 1 from 1 to 2").2,
+            "a crate like `[A]`"
+        );
+        assert_eq!(
+            error("    [A]\n[B]    \n 1   2 \n\nmove 1 from 1 to 2"),
+            (1, 5, "a crate resting on another crate".to_string())
+        );
+        assert_eq!(error("[A]\n\nmove 1 from 1 to 2").2, "a stack label");
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day5")).unwrap();
+        assert_eq!(part1(&input).unwrap(), "CMZ");
+    }
+
+    #[test]
+    fn test_empty_stack() {
+        let input = generator("    [A]\n 1   2 \n\nmove 1 from 2 to 1").unwrap();
+        assert_eq!(input.stacks[&1], vec![]);
+        assert_eq!(part1(&input).unwrap(), "A");
+    }
+
+    #[test]
+    fn test_invalid_move() {
+        let mut input = generator(example!("day5")).unwrap();
+        input.instructions.push(Instruction {
+            count: 10,
+            source: 1,
+            destination: 2,
+        });
+        let error = part1(&input).unwrap_err();
+        assert!(error.to_string().contains("only holds"), "{}", error);
+        assert!(part2(&input).is_err());
+    }
+
+    #[test]
+    fn test_cranes() {
+        let mut crates = vec!['A', 'B', 'C', 'D', 'E'];
+        CapacityLimited::new(2).unwrap().arrange(&mut crates);
+        assert_eq!(crates, ['D', 'E', 'B', 'C', 'A']);
+        assert!(CapacityLimited::new(0).is_err());
+
+        let input = generator(example!("day5")).unwrap();
+        let tops_with = |crane: &dyn Crane| tops(&simulate(&input, crane, |_, _| {}).unwrap());
+        assert_eq!(tops_with(&CapacityLimited::new(1).unwrap()), "CMZ");
+        assert_eq!(tops_with(&CapacityLimited::new(3).unwrap()), "MCD");
+        assert_eq!(tops_with(&CapacityLimited::new(2).unwrap()), "MCZ");
+    }
+
+    #[test]
+    fn test_unsimulate() {
+        let input = generator(example!("day5")).unwrap();
+        let cranes: [&dyn Crane; 4] = [
+            &CrateMover9000,
+            &CrateMover9001,
+            &CapacityLimited::new(2).unwrap(),
+            &CapacityLimited::new(3).unwrap(),
+        ];
+        for crane in cranes {
+            let end = validate(&input.stacks, &input.instructions, crane).unwrap();
+            assert_eq!(
+                unsimulate(&end, &input.instructions, crane).unwrap(),
+                input.stacks
+            );
+        }
+
+        let mut crates = vec!['A', 'B', 'C', 'D', 'E'];
+        let crane = CapacityLimited::new(2).unwrap();
+        crane.arrange(&mut crates);
+        crane.unarrange(&mut crates);
+        assert_eq!(crates, ['A', 'B', 'C', 'D', 'E']);
+
+        let error = unsimulate(&input.stacks, &input.instructions, &CrateMover9000).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "instruction 2 (`move 3 from 1 to 3`) cannot be undone: \
+             cannot move 3 crates from stack 3, which only holds 1"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let input = generator(example!("day5")).unwrap();
+        let mut start = input.stacks.clone();
+        start.get_mut(&2).unwrap().clear();
+        let error = validate(&start, &input.instructions, &CrateMover9001).unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "instruction 1 (`move 1 from 2 to 1`) is invalid: \
+             cannot move 1 crates from stack 2, which only holds 0"
+        );
+
+        let instructions = [Instruction {
+            count: 1,
+            source: 1,
+            destination: 4,
+        }];
+        let error = validate(&input.stacks, &instructions, &CrateMover9000).unwrap_err();
+        assert!(format!("{:#}", error).ends_with("there is no stack 4"));
+
+        let mut stacks = input.stacks.clone();
+        assert!(CrateMover9000.apply(&mut stacks, &instructions[0]).is_err());
+        assert_eq!(stacks, input.stacks);
+        assert!(unsimulate(&input.stacks, &instructions, &CrateMover9000).is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        let input = generator(example!("day5")).unwrap();
+        let states = trace(&input, &CrateMover9000).unwrap();
+        assert_eq!(states.len(), 4);
+        assert_eq!(states[0][&1], ['Z', 'N', 'D']);
+        assert_eq!(states[1][&3], ['P', 'D', 'N', 'Z']);
+        assert_eq!(tops(&states[3]), "CMZ");
+
+        let mut steps = vec![];
+        simulate(&input, &CrateMover9001, |instruction, stacks| {
+            steps.push(format!("{}: {}", instruction, tops(stacks)))
+        })
+        .unwrap();
+        assert_eq!(steps[0], "move 1 from 2 to 1: DCP");
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day5")).unwrap();
+        assert_eq!(part2(&input).unwrap(), "MCD");
+    }
 }
@@ -1,23 +1,218 @@
-use std::collections::HashSet;
+//! Day 6: Tuning Trouble.
+
+use std::io::{self, BufRead, BufReader, Bytes, Read};
 
 use aoc_runner_derive::aoc;
 
+/// Reads the datastream, dropping trailing newlines as aoc-runner does.
+pub fn read(mut reader: impl BufRead) -> io::Result<Vec<u8>> {
+    let mut input = vec![];
+    reader.read_to_end(&mut input)?;
+    while input.last() == Some(&b'\n') {
+        input.pop();
+    }
+
+    Ok(input)
+}
+
+/// The number of distinct bytes, and so the longest possible marker.
+const ALPHABET: usize = 256;
+
+/// Finds markers, windows of `length` distinct bytes, one byte at a time.
+/// Each byte costs O(1) whatever the window length.
+///
+/// A window of length 0 is a marker at every position, starting at 0 before
+/// any byte is seen. A window longer than 256 bytes can never be a marker.
+#[derive(Clone, Debug)]
+pub struct Detector {
+    length: usize,
+    /// The last `length` bytes, as a ring buffer.
+    window: Vec<u8>,
+    counts: [usize; ALPHABET],
+    distinct: usize,
+    position: usize,
+}
+
+impl Detector {
+    /// A detector for markers of `length` distinct bytes, before any byte
+    /// is seen.
+    pub fn new(length: usize) -> Self {
+        Self {
+            length,
+            window: Vec::with_capacity(length.min(ALPHABET)),
+            counts: [0; ALPHABET],
+            distinct: 0,
+            position: 0,
+        }
+    }
+
+    /// The number of bytes seen so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether the window holds a marker.
+    pub fn is_marker(&self) -> bool {
+        self.distinct == self.length
+    }
+
+    /// Adds `byte` to the window, returning whether the window now holds a
+    /// marker.
+    pub fn push(&mut self, byte: u8) -> bool {
+        self.position += 1;
+        if self.length == 0 || self.length > ALPHABET {
+            return self.is_marker();
+        }
+
+        if self.window.len() < self.length {
+            self.window.push(byte);
+        } else {
+            let slot = (self.position - 1) % self.length;
+            let old = std::mem::replace(&mut self.window[slot], byte);
+            self.counts[usize::from(old)] -= 1;
+            if self.counts[usize::from(old)] == 0 {
+                self.distinct -= 1;
+            }
+        }
+
+        self.counts[usize::from(byte)] += 1;
+        if self.counts[usize::from(byte)] == 1 {
+            self.distinct += 1;
+        }
+
+        self.is_marker()
+    }
+}
+
+/// Every marker in a stream, as the number of bytes read up to its end.
+pub struct Markers<R> {
+    bytes: Bytes<BufReader<R>>,
+    detector: Detector,
+    started: bool,
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.detector.is_marker() {
+                return Some(Ok(0));
+            }
+        }
+
+        for byte in self.bytes.by_ref() {
+            match byte {
+                Ok(byte) if self.detector.push(byte) => return Some(Ok(self.detector.position())),
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        None
+    }
+}
+
+/// Streams `reader` byte for byte, yielding the end of every window of
+/// `length` distinct bytes.
+pub fn markers<R: Read>(reader: R, length: usize) -> Markers<R> {
+    Markers {
+        bytes: BufReader::new(reader).bytes(),
+        detector: Detector::new(length),
+        started: false,
+    }
+}
+
 fn solve(input: &[u8], length: usize) -> Option<usize> {
+    let mut detector = Detector::new(length);
+    if detector.is_marker() {
+        return Some(0);
+    }
+
     input
-        .windows(length)
-        .position(|window| {
-            let set = window.iter().collect::<HashSet<_>>();
-            set.len() == length
-        })
-        .map(|i| i + length)
+        .iter()
+        .position(|&byte| detector.push(byte))
+        .map(|i| i + 1)
 }
 
+/// The number of characters processed before the first start-of-packet marker.
 #[aoc(day6, part1)]
-fn part1(input: &[u8]) -> Option<usize> {
+pub fn part1(input: &[u8]) -> Option<usize> {
     solve(input, 4)
 }
 
+/// The number of characters processed before the first start-of-message marker.
 #[aoc(day6, part2)]
-fn part2(input: &[u8]) -> Option<usize> {
+pub fn part2(input: &[u8]) -> Option<usize> {
     solve(input, 14)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let input = read("mjqjpqmgbljsphdztnvjfqwrcgsmlb\n".as_bytes()).unwrap();
+        assert_eq!(part1(&input), Some(7));
+    }
+
+    #[test]
+    fn test_markers() {
+        let input = "abcabdd";
+        let markers = |length| {
+            markers(input.as_bytes(), length)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(markers(3), [3, 4, 5, 6]);
+        assert_eq!(markers(4), [6]);
+        assert_eq!(markers(5), []);
+        assert_eq!(markers(1), [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(markers(0), [0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(markers(usize::MAX), []);
+        assert_eq!(solve(&[], 0), Some(0));
+        assert_eq!(solve(&[], 1), None);
+
+        let alphabet = (0..=255).collect::<Vec<u8>>();
+        assert_eq!(solve(&alphabet, 256), Some(256));
+        assert_eq!(solve(&alphabet, 257), None);
+    }
+
+    #[test]
+    fn test_markers_match_brute_force() {
+        let input = (0..2000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8 % 40 + 200)
+            .collect::<Vec<_>>();
+        for length in [1, 2, 4, 7, 14] {
+            let expected = input
+                .windows(length)
+                .enumerate()
+                .filter(|(_, window)| (0..length).all(|i| !window[i + 1..].contains(&window[i])))
+                .map(|(i, _)| i + length)
+                .collect::<Vec<_>>();
+            let found = markers(&input[..], length)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(found, expected, "length {}", length);
+            assert_eq!(solve(&input, length), expected.first().copied());
+        }
+    }
+
+    #[test]
+    fn test_part1() {
+        let expected = [7, 5, 6, 10, 11];
+        for (line, expected) in example!("day6").lines().zip(expected) {
+            assert_eq!(part1(line.as_bytes()), Some(expected), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_part2() {
+        let expected = [19, 23, 23, 29, 26];
+        for (line, expected) in example!("day6").lines().zip(expected) {
+            assert_eq!(part2(line.as_bytes()), Some(expected), "{}", line);
+        }
+    }
+}
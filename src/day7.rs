@@ -1,16 +1,26 @@
-use std::{collections::HashMap, path::PathBuf};
+//! Day 7: No Space Left On Device.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::bail;
+use eyre::ContextCompat;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
     character::complete::{space1, u32},
-    combinator::{map, value},
+    combinator::{map, rest, value, verify},
     sequence::{preceded, separated_pair, tuple},
-    Finish, IResult,
+    IResult,
 };
 
+use crate::error::{parse_complete, ParseError};
+
+const DISK_SIZE: usize = 70000000;
+const UPDATE_SIZE: usize = 30000000;
+
 #[derive(Clone)]
 enum Token {
     ChangeDirectory { target: String },
@@ -19,26 +29,39 @@ enum Token {
     OutputDirectory { name: String },
 }
 
-fn parse_path(input: &str) -> IResult<&str, String> {
+/// An entry name: the rest of the line, which may hold any character but `/`
+/// and may not be `.` or `..`.
+fn parse_name(input: &str) -> IResult<&str, String> {
     map(
-        take_while1(|c: char| c.is_ascii_alphabetic() || c == '.' || c == '/'),
+        verify(take_while1(|c: char| c != '/'), |name: &str| {
+            name != "." && name != ".."
+        }),
+        String::from,
+    )(input)
+}
+
+/// A `cd` target: the rest of the line, as a relative or absolute path.
+fn parse_target(input: &str) -> IResult<&str, String> {
+    map(
+        verify(rest, |target: &str| !target.is_empty()),
         String::from,
     )(input)
 }
 
 fn parse_command(input: &str) -> IResult<&str, Token> {
     let parse_ls = value(Token::List, tag("ls"));
-    let parse_cd = map(preceded(tuple((tag("cd"), space1)), parse_path), |target| {
-        Token::ChangeDirectory { target }
-    });
+    let parse_cd = map(
+        preceded(tuple((tag("cd"), space1)), parse_target),
+        |target| Token::ChangeDirectory { target },
+    );
     preceded(tuple((tag("$"), space1)), alt((parse_cd, parse_ls)))(input)
 }
 
 fn parse_output(input: &str) -> IResult<&str, Token> {
-    let parse_directory = map(preceded(tuple((tag("dir"), space1)), parse_path), |name| {
+    let parse_directory = map(preceded(tuple((tag("dir"), space1)), parse_name), |name| {
         Token::OutputDirectory { name }
     });
-    let parse_file = map(separated_pair(u32, space1, parse_path), |(size, name)| {
+    let parse_file = map(separated_pair(u32, space1, parse_name), |(size, name)| {
         Token::OutputFile { name, size }
     });
     alt((parse_directory, parse_file))(input)
@@ -76,61 +99,106 @@ impl Default for WalkState {
     }
 }
 
-fn tokenize(input: &str) -> eyre::Result<Vec<Token>> {
+fn tokenize(input: &str) -> Result<Vec<(&str, Token)>, ParseError> {
     input
         .lines()
-        .map(|line| match parse_token(line).finish() {
-            Ok((_, token)) => Ok(token),
-            Err(e) => bail!("error tokenizing {:?}: {}", line, e),
+        .map(|line| {
+            let token = parse_complete(
+                7,
+                input,
+                line,
+                parse_token,
+                "a command or a line of `ls` output",
+            )?;
+            Ok((line, token))
         })
-        .collect::<Result<Vec<Token>, _>>()
-}
-
-fn build_filesystem(tokens: Vec<Token>) -> eyre::Result<FileSystem> {
-    let state = tokens
-        .into_iter()
-        .try_fold(WalkState::default(), |mut walk, token| {
-            match token {
-                Token::ChangeDirectory { target } => {
-                    if target == ".." {
-                        walk.current_directory.pop();
-                    } else {
-                        walk.current_directory.push(target);
-                    }
-                }
-                Token::List => {}
-                Token::OutputFile { name, size } => {
-                    let path = walk.current_directory.join(&name);
-                    walk.filesystem.insert(path, Entry::File { size });
+        .collect()
+}
 
-                    if let Some(dir) = walk.filesystem.get_mut(&walk.current_directory) {
-                        let Entry::Directory { children, .. } = dir else {
-                            panic!("trying to add a file to a non-directory")
-                        };
+impl WalkState {
+    /// Records `name` in the current directory. Listing the same entry again
+    /// is fine, but a file and a directory cannot share a name.
+    fn add(&mut self, name: &str, entry: Entry) -> Result<(), String> {
+        let path = self.current_directory.join(name);
+        match (self.filesystem.get(&path), &entry) {
+            (None, _) => {}
+            (Some(Entry::Directory { .. }), Entry::Directory { .. })
+            | (Some(Entry::File { .. }), Entry::File { .. }) => return Ok(()),
+            (Some(Entry::Directory { .. }), Entry::File { .. }) => {
+                return Err(format!("a file, but `{}` is already a directory", name))
+            }
+            (Some(Entry::File { .. }), Entry::Directory { .. }) => {
+                return Err(format!("a directory, but `{}` is already a file", name))
+            }
+        }
+
+        self.filesystem.insert(path, entry);
+        if let Some(Entry::Directory { children }) =
+            self.filesystem.get_mut(&self.current_directory)
+        {
+            children.push(name.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Follows `cd target`. The target may start at the root with `/` or
+    /// `~` and may pass through several directories.
+    fn change_directory(&mut self, target: &str) -> Result<(), String> {
+        let (from_root, relative) = match target.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => (true, rest),
+            _ => (target.starts_with('/'), target),
+        };
+        if from_root {
+            self.current_directory = PathBuf::from("/");
+        }
 
-                        children.push(name);
+        for name in relative.split('/') {
+            match name {
+                "" | "." => {}
+                ".." => {
+                    if !self.current_directory.pop() {
+                        return Err("a directory to leave, but `/` is the root".to_string());
                     }
                 }
-                Token::OutputDirectory { name } => {
-                    let path = walk.current_directory.join(&name);
-                    walk.filesystem
-                        .entry(path)
-                        .or_insert_with(|| Entry::Directory { children: vec![] });
-
-                    if let Some(dir) = walk.filesystem.get_mut(&walk.current_directory) {
-                        let Entry::Directory { children, .. } = dir else {
-                            panic!("trying to add a file to a non-directory")
-                        };
-
-                        children.push(name);
-                    }
+                _ => {
+                    self.add(name, Entry::Directory { children: vec![] })
+                        .map_err(|_| format!("a directory, but `{}` is a file", name))?;
+                    self.current_directory.push(name);
                 }
             }
+        }
+
+        Ok(())
+    }
+}
 
-            Ok::<_, eyre::Report>(walk)
-        })?;
+fn build_filesystem(input: &str, tokens: Vec<(&str, Token)>) -> Result<FileSystem, ParseError> {
+    let mut walk = WalkState::default();
+    for (line, token) in tokens {
+        // Every name is at the end of its line.
+        let error = |name: &str, expected: String| {
+            ParseError::at(7, input, &line[line.len() - name.len()..], expected)
+        };
 
-    Ok(state.filesystem)
+        match token {
+            Token::ChangeDirectory { target } => {
+                walk.change_directory(&target)
+                    .map_err(|expected| error(&target, expected))?;
+            }
+            Token::List => {}
+            Token::OutputFile { name, size } => {
+                walk.add(&name, Entry::File { size })
+                    .map_err(|expected| error(&name, expected))?;
+            }
+            Token::OutputDirectory { name } => {
+                walk.add(&name, Entry::Directory { children: vec![] })
+                    .map_err(|expected| error(&name, expected))?;
+            }
+        }
+    }
+
+    Ok(walk.filesystem)
 }
 
 struct Search {
@@ -138,7 +206,7 @@ struct Search {
     current: PathBuf,
 }
 
-fn find_sizes(filesystem: &FileSystem) -> eyre::Result<HashMap<PathBuf, usize>> {
+fn find_sizes(filesystem: &FileSystem) -> HashMap<PathBuf, usize> {
     let mut sizes = HashMap::<PathBuf, usize>::new();
     let mut walk = filesystem
         .iter()
@@ -168,27 +236,111 @@ fn find_sizes(filesystem: &FileSystem) -> eyre::Result<HashMap<PathBuf, usize>>
         }
     }
 
-    Ok(sizes)
+    sizes
 }
 
+/// Replays the terminal transcript and returns the total size of every directory.
 #[aoc_generator(day7)]
-fn generator(input: &str) -> eyre::Result<HashMap<PathBuf, usize>> {
+pub fn generator(input: &str) -> Result<HashMap<PathBuf, usize>, ParseError> {
     let tokens = tokenize(input)?;
-    let filesystem = build_filesystem(tokens)?;
-    find_sizes(&filesystem)
+    let filesystem = build_filesystem(input, tokens)?;
+    Ok(find_sizes(&filesystem))
 }
 
+/// The summed size of every directory of at most 100000.
 #[aoc(day7, part1)]
-fn part1(input: &HashMap<PathBuf, usize>) -> usize {
+pub fn part1(input: &HashMap<PathBuf, usize>) -> usize {
     input.values().copied().filter(|&size| size <= 100000).sum()
 }
 
+/// The size of the smallest directory that frees enough space for the update.
 #[aoc(day7, part2)]
-fn part2(input: &HashMap<PathBuf, usize>) -> Option<usize> {
-    let root = PathBuf::from("/");
-    let used = input[&root];
-    let remaining = 70000000 - used;
-    let needed = 30000000 - remaining;
+pub fn part2(input: &HashMap<PathBuf, usize>) -> eyre::Result<usize> {
+    let used = input.get(Path::new("/")).copied().unwrap_or_default();
+    let free = DISK_SIZE
+        .checked_sub(used)
+        .with_context(|| format!("the files use {} bytes, more than the disk holds", used))?;
+    let needed = UPDATE_SIZE.saturating_sub(free);
+
+    input
+        .values()
+        .copied()
+        .filter(|&size| size >= needed)
+        .min()
+        .with_context(|| format!("no directory frees the {} bytes needed", needed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    input.values().copied().filter(|&size| size >= needed).min()
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day7")).unwrap();
+        assert_eq!(input[&PathBuf::from("/a/e")], 584);
+        assert_eq!(input[&PathBuf::from("/a")], 94853);
+        assert_eq!(input[&PathBuf::from("/d")], 24933642);
+        assert_eq!(input[&PathBuf::from("/")], 48381165);
+    }
+
+    #[test]
+    fn test_generator_error() {
+        let error = generator("$ cd /\n$ ls\n10 a\n$ cd a").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 6));
+        assert_eq!(error.expected, "a directory, but `a` is a file");
+
+        let error = generator("$ cd /\n$ ls\ndir a\n10 a").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 4));
+
+        let error = generator("$ cd /\n$ cd a\n$ cd ../..").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 6));
+        assert_eq!(error.expected, "a directory to leave, but `/` is the root");
+
+        let error = generator("$ cd /\n$ ls\ndir a/b").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 6));
+        assert!(generator("$ cd /\n$ ls\n10 ..").is_err());
+    }
+
+    #[test]
+    fn test_names_and_paths() {
+        let input = generator(
+            "$ cd /\n\
+             $ ls\n\
+             dir my-dir\n\
+             $ cd my-dir\n\
+             $ ls\n\
+             dir v2_data\n\
+             12 notes 1.txt\n\
+             $ cd ~\n\
+             $ cd /my-dir/v2_data\n\
+             $ ls\n\
+             30 a_b-c\n\
+             $ cd ../../my-dir/./new dir\n\
+             $ ls\n\
+             5 x\n\
+             $ cd ~/my-dir\n\
+             $ ls\n\
+             12 notes 1.txt",
+        )
+        .unwrap();
+        assert_eq!(input[&PathBuf::from("/my-dir/v2_data")], 30);
+        assert_eq!(input[&PathBuf::from("/my-dir/new dir")], 5);
+        assert_eq!(input[&PathBuf::from("/my-dir")], 47);
+        assert_eq!(input[&PathBuf::from("/")], 47);
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day7")).unwrap();
+        assert_eq!(part1(&input), 95437);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day7")).unwrap();
+        assert_eq!(part2(&input).unwrap(), 24933642);
+
+        let full = generator("$ cd /\n$ ls\n80000000 a").unwrap();
+        assert!(part2(&full).is_err());
+    }
 }
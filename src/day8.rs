@@ -1,60 +1,81 @@
-use std::{collections::HashMap, iter};
+//! Day 8: Treetop Tree House.
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::ContextCompat;
 use take_until::TakeUntilExt;
 
-type Point = (i32, i32);
-
-const DIRECTIONS: [Point; 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-
-fn neighbors_in_direction((x, y): Point, (dx, dy): Point) -> impl Iterator<Item = Point> {
-    iter::successors(Some((x + dx, y + dy)), move |(x, y)| Some((x + dx, y + dy)))
-}
+use crate::{
+    error::ParseError,
+    grid::{Grid, GridError, DIRECTIONS4},
+};
 
+/// Parses the grid of tree heights.
 #[aoc_generator(day8)]
-fn generator(input: &str) -> eyre::Result<HashMap<Point, u32>> {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars().enumerate().map(move |(x, c)| {
-                let value = c.to_digit(10)?;
-                Some(((x as i32, y as i32), value))
-            })
-        })
-        .collect::<Option<_>>()
-        .context("reading input")
+pub fn generator(input: &str) -> Result<Grid<u32>, ParseError> {
+    Grid::parse(input, |_, c| c.to_digit(10)).map_err(|e| match e {
+        GridError::InvalidCell(point) => ParseError::at_point(8, input, point, "a tree height 0-9"),
+        GridError::RaggedRow(point) => ParseError::at_point(8, input, point, "rows of equal width"),
+    })
 }
 
+/// The number of trees visible from outside the grid.
 #[aoc(day8, part1)]
-fn part1(input: &HashMap<Point, u32>) -> usize {
+pub fn part1(input: &Grid<u32>) -> usize {
     input
         .iter()
         .filter(|&(point, h)| {
-            DIRECTIONS.iter().any(|&delta| {
-                neighbors_in_direction(*point, delta)
-                    .take_while(|neighbor| input.contains_key(neighbor))
-                    .all(|neighbor| input[&neighbor] < *h)
-            })
+            DIRECTIONS4
+                .iter()
+                .any(|&delta| input.ray(point, delta).all(|neighbor| input[neighbor] < *h))
         })
         .count()
 }
 
+/// The highest scenic score of any tree.
 #[aoc(day8, part2)]
-fn part2(input: &HashMap<Point, u32>) -> Option<usize> {
+pub fn part2(input: &Grid<u32>) -> Option<usize> {
     input
         .iter()
         .map(|(point, h)| {
-            DIRECTIONS
+            DIRECTIONS4
                 .iter()
                 .map(|&delta| {
-                    neighbors_in_direction(*point, delta)
-                        .take_while(|neighbor| input.contains_key(neighbor))
-                        .take_until(|neighbor| input[neighbor] >= *h)
+                    input
+                        .ray(point, delta)
+                        .take_until(|&neighbor| input[neighbor] >= *h)
                         .count()
                 })
                 .product::<usize>()
         })
         .max()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day8")).unwrap();
+        assert_eq!((input.width(), input.height()), (5, 5));
+        assert_eq!(input[(3, 0)], 7);
+        assert_eq!(input[(0, 2)], 6);
+    }
+
+    #[test]
+    fn test_generator_error() {
+        let error = generator("303\n2a5").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day8")).unwrap();
+        assert_eq!(part1(&input), 21);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day8")).unwrap();
+        assert_eq!(part2(&input), Some(8));
+    }
+}
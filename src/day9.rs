@@ -1,7 +1,8 @@
-use std::{collections::HashSet, iter};
+//! Day 9: Rope Bridge.
+
+use std::{collections::HashSet, io::BufRead, iter};
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use eyre::bail;
 use nom::{
     branch::alt,
     character::complete::{char, space1, u32},
@@ -10,18 +11,28 @@ use nom::{
     IResult,
 };
 
-#[derive(Copy, Clone, Debug)]
-enum Direction {
+use crate::error::{collect_lines, parse_complete, ParseError, ReadError};
+
+/// A direction to move the head in.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// Towards positive `y`.
     Up,
+    /// Towards negative `y`.
     Down,
+    /// Towards negative `x`.
     Left,
+    /// Towards positive `x`.
     Right,
 }
 
-#[derive(Copy, Clone, Debug)]
-struct Instruction {
-    direction: Direction,
-    count: usize,
+/// Moves the head `count` steps in `direction`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Instruction {
+    /// The way to move.
+    pub direction: Direction,
+    /// The number of steps.
+    pub count: usize,
 }
 
 type Point = (i32, i32);
@@ -58,15 +69,19 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
     )(input)
 }
 
+fn parse_line(input: &str, line: &str) -> Result<Instruction, ParseError> {
+    parse_complete(9, input, line, parse_instruction, "a motion like `R 4`")
+}
+
+/// Parses the series of head motions.
 #[aoc_generator(day9)]
-fn generator(input: &str) -> eyre::Result<Vec<Instruction>> {
-    input
-        .lines()
-        .map(|line| match parse_instruction(line) {
-            Ok((_, i)) => Ok(i),
-            Err(e) => bail!("error parsing{:?}: {}", line, e),
-        })
-        .collect()
+pub fn generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    input.lines().map(|line| parse_line(input, line)).collect()
+}
+
+/// Streaming version of [`generator`].
+pub fn read(reader: impl BufRead) -> Result<Vec<Instruction>, ReadError> {
+    collect_lines(reader, |line| parse_line(line, line))
 }
 
 fn neighbors((x, y): Point) -> impl Iterator<Item = Point> {
@@ -127,19 +142,60 @@ fn solve<const N: usize>(input: &[Instruction]) -> usize {
     let state: State<N> = input.iter().fold(
         State::default(),
         |state, &Instruction { direction, count }| {
-            iter::repeat(direction).take(count).fold(state, move_knot)
+            iter::repeat_n(direction, count).fold(state, move_knot)
         },
     );
 
     state.visited.len()
 }
 
+/// The number of positions visited by the tail of a two-knot rope.
 #[aoc(day9, part1)]
-fn part1(input: &[Instruction]) -> usize {
+pub fn part1(input: &[Instruction]) -> usize {
     solve::<2>(input)
 }
 
+/// The number of positions visited by the tail of a ten-knot rope.
 #[aoc(day9, part2)]
-fn part2(input: &[Instruction]) -> usize {
+pub fn part2(input: &[Instruction]) -> usize {
     solve::<10>(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator() {
+        let input = generator(example!("day9")).unwrap();
+        assert_eq!(input.len(), 8);
+        assert!(matches!(
+            input[0],
+            Instruction {
+                direction: Direction::Right,
+                count: 4
+            }
+        ));
+    }
+
+    #[test]
+    fn test_read() {
+        let input = example!("day9");
+        assert_eq!(read(input.as_bytes()).unwrap(), generator(input).unwrap());
+    }
+
+    #[test]
+    fn test_part1() {
+        let input = generator(example!("day9")).unwrap();
+        assert_eq!(part1(&input), 13);
+    }
+
+    #[test]
+    fn test_part2() {
+        let input = generator(example!("day9")).unwrap();
+        assert_eq!(part2(&input), 1);
+
+        let input = generator(example!("day9-larger")).unwrap();
+        assert_eq!(part2(&input), 36);
+    }
+}
@@ -0,0 +1,265 @@
+//! Errors for malformed puzzle input, pointing at where parsing failed.
+
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead},
+};
+
+use nom::{combinator::all_consuming, Finish, Parser};
+
+use crate::grid::Point;
+
+/// A malformed puzzle input, pinned to the 1-based line and column of the
+/// first byte that could not be parsed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// The day whose input failed to parse.
+    pub day: u8,
+    /// The 1-based line of the failure.
+    pub line: usize,
+    /// The 1-based column of the failure, in characters.
+    pub column: usize,
+    /// The whole line the failure is on.
+    pub snippet: String,
+    /// What the parser was looking for, such as `a calorie count`.
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Builds an error pointing at `offset` bytes into `input`.
+    pub fn new(day: u8, input: &str, offset: usize, expected: impl Into<String>) -> Self {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let (before, after) = input.split_at(offset);
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or_default();
+        let line = before.matches('\n').count() + 1;
+        let column = before[line_start..].chars().count() + 1;
+        let line_end = after.find('\n').map(|i| offset + i).unwrap_or(input.len());
+        let snippet = input[line_start..line_end]
+            .trim_end_matches('\r')
+            .to_string();
+
+        Self {
+            day,
+            line,
+            column,
+            snippet,
+            expected: expected.into(),
+        }
+    }
+
+    /// Builds an error pointing at the start of `position`, which must be a
+    /// slice of `input` (as handed back by nom or `str::lines`).
+    pub fn at(day: u8, input: &str, position: &str, expected: impl Into<String>) -> Self {
+        let offset = (position.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .unwrap_or(input.len());
+        Self::new(day, input, offset, expected)
+    }
+
+    /// Builds an error pointing at character `x` of line `y` (both 0-based).
+    pub fn at_point(day: u8, input: &str, (x, y): Point, expected: impl Into<String>) -> Self {
+        let line_start = input
+            .split_inclusive('\n')
+            .take(y as usize)
+            .map(str::len)
+            .sum::<usize>();
+        let offset = input[line_start..]
+            .char_indices()
+            .map(|(i, _)| line_start + i)
+            .nth(x as usize)
+            .unwrap_or_else(|| {
+                input[line_start..]
+                    .find('\n')
+                    .map_or(input.len(), |i| line_start + i)
+            });
+        Self::new(day, input, offset, expected)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = self.line.to_string();
+        let gutter = " ".repeat(line.len());
+        let indent = self
+            .snippet
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        writeln!(f, "error: expected {}", self.expected)?;
+        writeln!(
+            f,
+            "{gutter}--> day {}, line {}, column {}",
+            self.day, self.line, self.column
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line} | {}", self.snippet)?;
+        write!(f, "{gutter} | {indent}^")
+    }
+}
+
+impl Error for ParseError {}
+
+/// A failure while streaming puzzle input from a reader.
+#[derive(Debug)]
+pub enum ReadError {
+    /// The reader failed.
+    Io(io::Error),
+    /// A line could not be parsed.
+    Parse(ParseError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "error: reading input: {}", e),
+            ReadError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(e: ParseError) -> Self {
+        ReadError::Parse(e)
+    }
+}
+
+/// Feeds each line of `reader` to `f` without its line ending, reusing a
+/// single buffer. Blank lines are held back until a later line follows them,
+/// so trailing newlines are ignored just as the `&str` generators see them.
+///
+/// `f` parses the line on its own; any error it returns is moved to the
+/// line's real number.
+pub(crate) fn read_lines<R, F>(mut reader: R, mut f: F) -> Result<(), ReadError>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Result<(), ParseError>,
+{
+    let mut buffer = String::new();
+    let mut number = 0;
+    let mut blanks = 0;
+    loop {
+        buffer.clear();
+        if reader.read_line(&mut buffer)? == 0 {
+            return Ok(());
+        }
+
+        number += 1;
+        let line = buffer.strip_suffix('\n').unwrap_or(&buffer);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            blanks += 1;
+            continue;
+        }
+
+        let first = number - blanks;
+        for (i, line) in (first..number).map(|n| (n, "")).chain([(number, line)]) {
+            f(line).map_err(|e| ReadError::Parse(ParseError { line: i, ..e }))?;
+        }
+        blanks = 0;
+    }
+}
+
+/// Parses every line of `reader` with `f` and collects the results.
+pub(crate) fn collect_lines<R, T, C, F>(reader: R, mut f: F) -> Result<C, ReadError>
+where
+    R: BufRead,
+    C: Default + Extend<T>,
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    let mut output = C::default();
+    read_lines(reader, |line| {
+        output.extend([f(line)?]);
+        Ok(())
+    })?;
+    Ok(output)
+}
+
+/// Runs `parser` over all of `fragment`, a slice of `input`, reporting any
+/// failure or trailing input as a [`ParseError`] against `input`.
+pub(crate) fn parse_complete<'a, O, P>(
+    day: u8,
+    input: &'a str,
+    fragment: &'a str,
+    parser: P,
+    expected: &str,
+) -> Result<O, ParseError>
+where
+    P: Parser<&'a str, O, nom::error::Error<&'a str>>,
+{
+    all_consuming(parser)(fragment)
+        .finish()
+        .map(|(_, output)| output)
+        .map_err(|e| ParseError::at(day, input, e.input, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let input = "abc\ndef\nghi";
+        let error = ParseError::at(1, input, &input[5..], "a letter");
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.snippet, "def");
+
+        let error = ParseError::at_point(1, input, (2, 2), "a letter");
+        assert_eq!((error.line, error.column), (3, 3));
+        assert_eq!(error.snippet, "ghi");
+    }
+
+    #[test]
+    fn test_read_lines() {
+        let mut lines = vec![];
+        read_lines("a\r\n\nb\n\n\n".as_bytes(), |line| {
+            lines.push(line.to_string());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(lines, vec!["a", "", "b"]);
+
+        let error = read_lines("a\n\nb".as_bytes(), |line| match line {
+            "" => Err(ParseError::new(1, line, 0, "a letter")),
+            _ => Ok(()),
+        })
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ReadError::Parse(ParseError { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::new(4, "2-4,6-8\n2-3,4x5", 13, "a range");
+        let expected = "\
+error: expected a range
+ --> day 4, line 2, column 6
+  |
+2 | 2-3,4x5
+  |      ^";
+        assert_eq!(error.to_string(), expected);
+    }
+}
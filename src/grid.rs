@@ -0,0 +1,250 @@
+//! A dense 2D grid for the puzzles whose input is a map of characters.
+
+use std::{
+    error::Error,
+    fmt, iter,
+    ops::{Index, IndexMut},
+};
+
+/// An `(x, y)` position, with `x` growing rightwards and `y` downwards.
+pub type Point = (i32, i32);
+
+/// The steps to the four orthogonal neighbors.
+pub const DIRECTIONS4: [Point; 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+/// The steps to the eight orthogonal and diagonal neighbors.
+pub const DIRECTIONS8: [Point; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Why [`Grid::parse`] rejected its input.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GridError {
+    /// The cell callback rejected the character at this point.
+    InvalidCell(Point),
+    /// The row containing this point is not as wide as the first row.
+    RaggedRow(Point),
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::InvalidCell((x, y)) => write!(f, "invalid cell at ({}, {})", x, y),
+            GridError::RaggedRow((x, y)) => write!(f, "ragged row at ({}, {})", x, y),
+        }
+    }
+}
+
+impl Error for GridError {}
+
+/// A dense, row-major 2D grid addressed by `(x, y)` points.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `value`.
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from a character grid, one row per line, converting each
+    /// character with `f`.
+    pub fn parse<F>(input: &str, mut f: F) -> Result<Self, GridError>
+    where
+        F: FnMut(Point, char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for (y, line) in input.lines().enumerate() {
+            let before = cells.len();
+            for (x, c) in line.chars().enumerate() {
+                let point = (x as i32, y as i32);
+                if width.is_some_and(|width| x >= width) {
+                    return Err(GridError::RaggedRow(point));
+                }
+
+                cells.push(f(point, c).ok_or(GridError::InvalidCell(point))?);
+            }
+
+            let length = cells.len() - before;
+            if *width.get_or_insert(length) != length {
+                return Err(GridError::RaggedRow((length as i32, y as i32)));
+            }
+
+            height += 1;
+        }
+
+        Ok(Self {
+            width: width.unwrap_or_default(),
+            height,
+            cells,
+        })
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn offset(&self, (x, y): Point) -> Option<usize> {
+        let x = usize::try_from(x).ok().filter(|&x| x < self.width)?;
+        let y = usize::try_from(y).ok().filter(|&y| y < self.height)?;
+        Some(y * self.width + x)
+    }
+
+    /// Whether `point` is inside the grid.
+    pub fn contains(&self, point: Point) -> bool {
+        self.offset(point).is_some()
+    }
+
+    /// The cell at `point`, or `None` outside the grid.
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.offset(point).map(|i| &self.cells[i])
+    }
+
+    /// The cell at `point` for writing, or `None` outside the grid.
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.offset(point).map(|i| &mut self.cells[i])
+    }
+
+    /// Every point in the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| ((i % width) as i32, (i / width) as i32))
+    }
+
+    /// Every cell with its point, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// The in-bounds orthogonal neighbors of `point`.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.offsets(point, &DIRECTIONS4)
+    }
+
+    /// The in-bounds orthogonal and diagonal neighbors of `point`.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.offsets(point, &DIRECTIONS8)
+    }
+
+    fn offsets<'a>(
+        &'a self,
+        (x, y): Point,
+        deltas: &'static [Point],
+    ) -> impl Iterator<Item = Point> + 'a {
+        deltas
+            .iter()
+            .map(move |&(dx, dy)| (x + dx, y + dy))
+            .filter(|&n| self.contains(n))
+    }
+
+    /// Walks from `point` in steps of `delta`, excluding `point` itself and
+    /// stopping at the edge of the grid. A zero `delta` never moves, so its
+    /// ray is empty.
+    pub fn ray(&self, (x, y): Point, (dx, dy): Point) -> impl Iterator<Item = Point> + '_ {
+        let first = ((dx, dy) != (0, 0)).then_some((x + dx, y + dy));
+        iter::successors(first, move |(x, y)| Some((x + dx, y + dy)))
+            .take_while(|&p| self.contains(p))
+    }
+
+    /// Draws the grid with one character per cell from `f`, ending each row
+    /// with a newline.
+    pub fn render<F>(&self, f: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut output = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            output.extend(row.iter().map(&f));
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        self.get(point).expect("point out of bounds")
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        self.get_mut(point).expect("point out of bounds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("ab\ncd", |_, c| Some(c)).unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert_eq!(grid[(1, 0)], 'b');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, -1)), None);
+        assert_eq!(grid.render(|&c| c), "ab\ncd\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let invalid = Grid::parse("12\n3x", |_, c| c.to_digit(10));
+        assert_eq!(invalid, Err(GridError::InvalidCell((1, 1))));
+
+        let short = Grid::parse("12\n3", |_, c| c.to_digit(10));
+        assert_eq!(short, Err(GridError::RaggedRow((1, 1))));
+
+        let long = Grid::parse("12\n345", |_, c| c.to_digit(10));
+        assert_eq!(long, Err(GridError::RaggedRow((2, 1))));
+        assert_eq!(
+            GridError::InvalidCell((1, 1)).to_string(),
+            "invalid cell at (1, 1)"
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(grid.neighbors4((0, 0)).count(), 2);
+        assert_eq!(grid.neighbors4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbors8((0, 0)).count(), 3);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = Grid::new(4, 3, 0);
+        let ray = grid.ray((1, 1), (1, 0)).collect::<Vec<_>>();
+        assert_eq!(ray, [(2, 1), (3, 1)]);
+        assert_eq!(grid.ray((0, 0), (-1, 0)).count(), 0);
+        assert_eq!(grid.ray((1, 1), (0, 0)).count(), 0);
+    }
+}
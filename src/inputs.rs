@@ -0,0 +1,713 @@
+//! Random, well-formed puzzle inputs for property and stress testing.
+//!
+//! Every generator is deterministic for a given seed. `size` scales the
+//! amount of input (lines, monkeys, valves, ...) in whatever way suits the day.
+
+use std::{collections::HashSet, fmt::Write, ops::RangeInclusive};
+
+/// A small SplitMix64 generator, so generated inputs stay reproducible no
+/// matter which versions of other crates are in use.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// A generator that always gives the same values for the same `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// The next uniformly random 64 bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly random value in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        let span = end.abs_diff(start) + 1;
+        start.wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    /// A random index into something of `length` items, which must not be
+    /// empty.
+    pub fn index(&mut self, length: usize) -> usize {
+        self.range(0..=length as i64 - 1) as usize
+    }
+
+    /// True with a probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator < numerator
+    }
+
+    /// A random item of `items`, which must not be empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    /// Puts `items` in a random order.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+
+    fn letter(&mut self) -> char {
+        (b'a' + self.range(0..=25) as u8) as char
+    }
+}
+
+const ITEMS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Generates input for `day`, or `None` if there is no generator for it.
+pub fn input(day: u8, rng: &mut Rng, size: usize) -> Option<String> {
+    let generator = match day {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+        11 => day11,
+        12 => day12,
+        13 => day13,
+        14 => day14,
+        15 => day15,
+        16 => day16,
+        _ => return None,
+    };
+
+    Some(generator(rng, size))
+}
+
+/// `size` elves, each carrying one to ten items.
+pub fn day1(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            (0..rng.range(1..=10))
+                .map(|_| rng.range(1000..=60000).to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// `size` rounds of the strategy guide.
+pub fn day2(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            format!(
+                "{} {}",
+                rng.choose(&['A', 'B', 'C']),
+                rng.choose(&['X', 'Y', 'Z'])
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `size` groups of three rucksacks. Each sack has exactly one item type in
+/// both compartments and each group has exactly one badge.
+pub fn day3(rng: &mut Rng, size: usize) -> String {
+    let mut sacks = vec![];
+    for _ in 0..size.max(1) {
+        let mut alphabet = ITEMS.to_vec();
+        rng.shuffle(&mut alphabet);
+        let badge = alphabet.pop().unwrap_or(b'a');
+
+        // Each sack draws from its own third of the alphabet, so the badge is
+        // the only item all three share.
+        for pool in alphabet.chunks(alphabet.len() / 3).take(3) {
+            let (left_pool, right_pool) = pool.split_at(pool.len() / 2);
+            let shared = left_pool[0];
+            let half = rng.range(2..=16) as usize;
+
+            let mut left = vec![shared];
+            let mut right = vec![shared];
+            if rng.chance(1, 2) {
+                left.push(badge);
+            } else {
+                right.push(badge);
+            }
+
+            while left.len() < half {
+                left.push(*rng.choose(&left_pool[1..]));
+            }
+            while right.len() < half {
+                right.push(*rng.choose(&right_pool[1..]));
+            }
+
+            rng.shuffle(&mut left);
+            rng.shuffle(&mut right);
+            left.extend(right);
+            sacks.push(String::from_utf8(left).unwrap_or_default());
+        }
+    }
+
+    sacks.join("\n")
+}
+
+/// `size` pairs of section assignments between 1 and 99.
+pub fn day4(rng: &mut Rng, size: usize) -> String {
+    let range = |rng: &mut Rng| {
+        let start = rng.range(1..=99);
+        let end = rng.range(start..=99);
+        format!("{}-{}", start, end)
+    };
+
+    (0..size.max(1))
+        .map(|_| format!("{},{}", range(rng), range(rng)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Up to nine stacks and `size` instructions that never move more crates
+/// than a stack holds.
+pub fn day5(rng: &mut Rng, size: usize) -> String {
+    let count = rng.range(2..=9) as usize;
+    let mut stacks = (0..count)
+        .map(|_| {
+            (0..rng.range(1..=8))
+                .map(|_| (b'A' + rng.range(0..=25) as u8) as char)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let height = stacks.iter().map(Vec::len).max().unwrap_or_default();
+    let mut output = String::new();
+    for row in (0..height).rev() {
+        let line = stacks
+            .iter()
+            .map(|stack| match stack.get(row) {
+                Some(c) => format!("[{}]", c),
+                None => "   ".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(output, "{}", line).unwrap();
+    }
+
+    let labels = (1..=count)
+        .map(|n| format!(" {} ", n))
+        .collect::<Vec<_>>()
+        .join(" ");
+    writeln!(output, "{}", labels).unwrap();
+
+    for _ in 0..size.max(1) {
+        let sources = (0..count)
+            .filter(|&i| !stacks[i].is_empty())
+            .collect::<Vec<_>>();
+        let source = *rng.choose(&sources);
+        let mut destination = rng.index(count - 1);
+        if destination >= source {
+            destination += 1;
+        }
+
+        let moving = rng.range(1..=stacks[source].len() as i64) as usize;
+        let split = stacks[source].len() - moving;
+        let crates = stacks[source].split_off(split);
+        stacks[destination].extend(crates);
+        write!(
+            output,
+            "\nmove {} from {} to {}",
+            moving,
+            source + 1,
+            destination + 1
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+/// A datastream of at least `size` lowercase letters, holding 14 distinct
+/// letters in a row somewhere so that both markers exist.
+pub fn day6(rng: &mut Rng, size: usize) -> String {
+    const MARKER: usize = 14;
+    let mut stream = (0..size.max(MARKER))
+        .map(|_| rng.letter())
+        .collect::<Vec<_>>();
+    let mut alphabet = ('a'..='z').collect::<Vec<_>>();
+    rng.shuffle(&mut alphabet);
+    let start = rng.index(stream.len() - MARKER + 1);
+    stream[start..start + MARKER].copy_from_slice(&alphabet[..MARKER]);
+    stream.into_iter().collect()
+}
+
+/// A terminal transcript exploring a consistent tree of `size` directories,
+/// with a total size the update can always make room for.
+pub fn day7(rng: &mut Rng, size: usize) -> String {
+    struct Directory {
+        files: Vec<(String, u64)>,
+        children: Vec<(String, usize)>,
+    }
+
+    fn name(rng: &mut Rng, taken: &mut HashSet<String>, extension: bool) -> String {
+        loop {
+            let mut name = (0..rng.range(1..=8))
+                .map(|_| rng.letter())
+                .collect::<String>();
+            if extension && rng.chance(1, 2) {
+                name.push('.');
+                name.extend((0..3).map(|_| rng.letter()));
+            }
+
+            if taken.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    let mut directories = vec![Directory {
+        files: vec![],
+        children: vec![],
+    }];
+    let mut names = vec![HashSet::new()];
+    for i in 1..size.max(1) {
+        let parent = rng.index(i);
+        let child = name(rng, &mut names[parent], false);
+        directories[parent].children.push((child, i));
+        directories.push(Directory {
+            files: vec![],
+            children: vec![],
+        });
+        names.push(HashSet::new());
+    }
+
+    // Spread a total of 41-69 million across the files, keeping clear of the
+    // 40 and 70 million limits where part 2 has no answer.
+    let file_count = size.max(1) * 3;
+    let weights = (0..file_count)
+        .map(|_| rng.range(1..=1000) as u64)
+        .collect::<Vec<_>>();
+    let total = rng.range(41_000_000..=69_000_000) as u64;
+    let weight_sum = weights.iter().sum::<u64>();
+    for weight in weights {
+        let directory = rng.index(directories.len());
+        let file = name(rng, &mut names[directory], true);
+        let size = (total * weight / weight_sum).max(1);
+        directories[directory].files.push((file, size));
+    }
+
+    fn walk(directories: &[Directory], index: usize, output: &mut Vec<String>) {
+        let directory = &directories[index];
+        output.push("$ ls".to_string());
+        for (name, _) in &directory.children {
+            output.push(format!("dir {}", name));
+        }
+        for (name, size) in &directory.files {
+            output.push(format!("{} {}", size, name));
+        }
+
+        for (name, child) in &directory.children {
+            output.push(format!("$ cd {}", name));
+            walk(directories, *child, output);
+            output.push("$ cd ..".to_string());
+        }
+    }
+
+    let mut output = vec!["$ cd /".to_string()];
+    walk(&directories, 0, &mut output);
+    output.join("\n")
+}
+
+/// A `size` by `size` grid of tree heights.
+pub fn day8(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| char::from(b'0' + rng.range(0..=9) as u8))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `size` head motions of one to twenty steps.
+pub fn day9(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            format!(
+                "{} {}",
+                rng.choose(&['U', 'D', 'L', 'R']),
+                rng.range(1..=20)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A program of `size` instructions.
+pub fn day10(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            if rng.chance(1, 3) {
+                "noop".to_string()
+            } else {
+                format!("addx {}", rng.range(-20..=20))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Between four and eight monkeys, each with up to `size` starting items.
+/// Every monkey throws to two other monkeys and tests a distinct prime.
+///
+/// Like the real inputs, one monkey squares and two others multiply. Nobody
+/// throws to the squaring monkey and the multipliers are at most 6, so part
+/// 1's worry levels stay well inside a `u64`.
+pub fn day11(rng: &mut Rng, size: usize) -> String {
+    let count = rng.range(4..=8) as usize;
+    let mut primes = vec![2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut primes);
+    let mut order = (0..count).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    let (squarer, multipliers) = (order[0], &order[1..3]);
+
+    (0..count)
+        .map(|i| {
+            let items = (0..rng.range(1..=size.max(1) as i64))
+                .map(|_| rng.range(50..=99).to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let operation = if i == squarer {
+                "old * old".to_string()
+            } else if multipliers.contains(&i) {
+                format!("old * {}", rng.range(2..=6))
+            } else {
+                format!("old + {}", rng.range(1..=8))
+            };
+
+            let mut targets = (0..count)
+                .filter(|&t| t != i && t != squarer)
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut targets);
+
+            format!(
+                "Monkey {}:\n  Starting items: {}\n  Operation: new = {}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}",
+                i, items, operation, primes[i], targets[0], targets[1]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// A heightmap `size` rows tall with a climbable route from `S` to `E`.
+pub fn day12(rng: &mut Rng, size: usize) -> String {
+    let width = rng.range(26..=40) as usize;
+    let height = size.max(1);
+    let route = rng.index(height);
+
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| match (x, y == route) {
+                    (0, true) => 'S',
+                    (x, true) if x == width - 1 => 'E',
+                    (x, true) => char::from(b'a' + (x * 25 / (width - 1)) as u8),
+                    _ => rng.letter(),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn packet(rng: &mut Rng, depth: usize) -> String {
+    if depth > 0 && rng.chance(1, 2) {
+        return rng.range(0..=10).to_string();
+    }
+
+    let items = if depth >= 4 { 0 } else { rng.range(0..=4) };
+    let items = (0..items)
+        .map(|_| packet(rng, depth + 1))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("[{}]", items)
+}
+
+/// `size` pairs of packets.
+pub fn day13(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{}\n{}", packet(rng, 0), packet(rng, 0)))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// `size` rock paths below the sand source.
+///
+/// Rocks stay within 40 columns of the source but at least 45 rows below it,
+/// so the pile always spills past them into the abyss in part 1.
+pub fn day14(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let (mut x, mut y) = (rng.range(470..=530), rng.range(45..=100));
+            let mut points = vec![format!("{},{}", x, y)];
+            for i in 0..rng.range(1..=4) {
+                let step = rng.range(1..=8) * if rng.chance(1, 2) { 1 } else { -1 };
+                if i % 2 == 0 {
+                    x = (x + step).clamp(460, 540);
+                } else {
+                    y = (y + step).clamp(45, 120);
+                }
+
+                points.push(format!("{},{}", x, y));
+            }
+
+            points.join(" -> ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Four sensors that cover every spot with coordinates from 0 to 4,000,000
+/// except one, and `size - 4` more with small ranges beyond them.
+///
+/// The sensors are placed in rotated coordinates `u = y - x`, `v = y + x`,
+/// where each sensor covers a square. Two cover every spot with a `u` on
+/// either side of the hidden spot's, and two cover the spots above and
+/// below it along its own diagonal. Each beacon sits on the far edge of its
+/// sensor's square, past the reach of every other sensor.
+pub fn day15(rng: &mut Rng, size: usize) -> String {
+    const LIMIT: i64 = 4_000_000;
+    let point = |u: i64, v: i64| ((v - u) / 2, (v + u) / 2);
+    let (x, y) = (rng.range(0..=LIMIT), rng.range(0..=LIMIT));
+    let (u, v) = (y - x, y + x);
+
+    // `u` spans -LIMIT..=LIMIT and `v` spans 0..=2 * LIMIT across the square.
+    // A sensor's `u` and `v` must share a parity to be a whole point.
+    let side = 2 * LIMIT;
+    let center = |u: i64| LIMIT + (u - LIMIT).rem_euclid(2);
+    let mut reports = vec![];
+    for sign in [-1, 1] {
+        let su = u + sign * (side + 1);
+        let sv = center(su);
+        reports.push((point(su, sv), point(su + sign * side, sv)));
+    }
+    for sign in [-1, 1] {
+        let reach = side + 1;
+        let sv = v + sign * (reach + 1);
+        reports.push((point(u, sv), point(u + 1, sv + sign * reach)));
+    }
+
+    // Further sensors line up past the left square's far edge, spaced so
+    // that none reaches another's beacon.
+    let far = u - 3 * side - 2;
+    for i in 0..size.saturating_sub(4) as i64 {
+        let su = far - 2002 * i;
+        let sensor = point(su, center(su));
+        let reach = rng.range(1..=1000);
+        let dx = rng.range(0..=reach);
+        reports.push((sensor, (sensor.0 + dx, sensor.1 - (reach - dx))));
+    }
+
+    rng.shuffle(&mut reports);
+    reports
+        .into_iter()
+        .map(|((sx, sy), (bx, by))| {
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+                sx, sy, bx, by
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A connected network of `size` valves including `AA`, listed in random
+/// order, with about half of them having a flow rate.
+pub fn day16(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(2, 26 * 26);
+    let mut names = vec!["AA".to_string()];
+    let mut taken = HashSet::from(["AA".to_string()]);
+    while names.len() < size {
+        let name = format!(
+            "{}{}",
+            (b'A' + rng.range(0..=25) as u8) as char,
+            (b'A' + rng.range(0..=25) as u8) as char
+        );
+        if taken.insert(name.clone()) {
+            names.push(name);
+        }
+    }
+
+    // A random spanning tree keeps the network connected, then a few extra
+    // tunnels add cycles.
+    let mut tunnels = vec![vec![]; size];
+    let connect = |a: usize, b: usize, tunnels: &mut [Vec<usize>]| {
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
+        }
+    };
+    for i in 1..size {
+        let j = rng.index(i);
+        connect(i, j, &mut tunnels);
+    }
+    for _ in 0..size / 2 {
+        let (a, b) = (rng.index(size), rng.index(size));
+        connect(a, b, &mut tunnels);
+    }
+
+    let mut order = (0..size).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    order
+        .into_iter()
+        .map(|i| {
+            let rate = if i != 0 && rng.chance(1, 2) {
+                rng.range(1..=25)
+            } else {
+                0
+            };
+            let targets = tunnels[i]
+                .iter()
+                .map(|&t| names[t].as_str())
+                .collect::<Vec<_>>();
+            let lead = if targets.len() == 1 {
+                "tunnel leads to valve"
+            } else {
+                "tunnels lead to valves"
+            };
+
+            format!(
+                "Valve {} has flow rate={}; {} {}",
+                names[i],
+                rate,
+                lead,
+                targets.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day1, day10, day15, day2, day3, day4, day5, day9, runner};
+
+    #[test]
+    fn test_deterministic() {
+        for day in 1..=16 {
+            let a = input(day, &mut Rng::new(7), 8);
+            let b = input(day, &mut Rng::new(7), 8);
+            assert_eq!(a, b, "day {}", day);
+        }
+    }
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        for solution in runner::solutions() {
+            for seed in 0..10 {
+                let mut rng = Rng::new(seed);
+                let input = input(solution.day, &mut rng, 10).unwrap();
+                let parsed = solution
+                    .generate(&input)
+                    .unwrap_or_else(|e| panic!("seed {}:\n{}", seed, e));
+                for part in [1, 2] {
+                    let answer = solution.solve(part, parsed.as_ref());
+                    assert!(
+                        answer.is_ok(),
+                        "day {} part {} seed {}: {:?}",
+                        solution.day,
+                        part,
+                        seed,
+                        answer
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_generator() {
+        macro_rules! check {
+            ($module:ident, $seed:expr) => {
+                let input = $module(&mut Rng::new($seed), 20) + "\n";
+                let expected = $module::generator(input.trim_end_matches('\n')).unwrap();
+                assert_eq!($module::read(input.as_bytes()).unwrap(), expected);
+            };
+        }
+
+        for seed in 0..10 {
+            check!(day1, seed);
+            check!(day2, seed);
+            check!(day3, seed);
+            check!(day4, seed);
+            check!(day9, seed);
+            check!(day10, seed);
+            check!(day15, seed);
+        }
+    }
+
+    #[test]
+    fn test_day3_structure() {
+        for seed in 0..20 {
+            let input = day3(&mut Rng::new(seed), 5);
+            let sacks = input.lines().collect::<Vec<_>>();
+            for sack in &sacks {
+                assert_eq!(sack.len() % 2, 0);
+                let (left, right) = sack.split_at(sack.len() / 2);
+                let left = left.chars().collect::<HashSet<_>>();
+                let right = right.chars().collect::<HashSet<_>>();
+                assert_eq!(left.intersection(&right).count(), 1, "{}", sack);
+            }
+
+            for group in sacks.chunks(3) {
+                let badges = group
+                    .iter()
+                    .map(|sack| sack.chars().collect::<HashSet<_>>())
+                    .reduce(|acc, sack| &acc & &sack)
+                    .unwrap();
+                assert_eq!(badges.len(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_day5_matches_brute_force() {
+        for seed in 0..20 {
+            let input = day5::generator(&day5(&mut Rng::new(seed), 30)).unwrap();
+
+            // Replay with plain pops and pushes: one crate at a time for part 1,
+            // and through a holding stack, which keeps the order, for part 2.
+            let mut single = input.stacks.clone();
+            let mut grouped = input.stacks.clone();
+            for instruction in &input.instructions {
+                let mut held = vec![];
+                for _ in 0..instruction.count {
+                    let c = single.get_mut(&instruction.source).unwrap().pop().unwrap();
+                    single.get_mut(&instruction.destination).unwrap().push(c);
+                    held.push(grouped.get_mut(&instruction.source).unwrap().pop().unwrap());
+                }
+                while let Some(c) = held.pop() {
+                    grouped.get_mut(&instruction.destination).unwrap().push(c);
+                }
+            }
+
+            let tops = |stacks: &day5::Stacks| {
+                stacks
+                    .values()
+                    .filter_map(|stack| stack.last())
+                    .collect::<String>()
+            };
+            assert_eq!(day5::part1(&input).unwrap(), tops(&single), "seed {}", seed);
+            assert_eq!(
+                day5::part2(&input).unwrap(),
+                tops(&grouped),
+                "seed {}",
+                seed
+            );
+        }
+    }
+}
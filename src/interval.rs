@@ -0,0 +1,180 @@
+//! Inclusive ranges of IDs and unions of them, without materializing IDs.
+
+/// A non-empty, inclusive range of IDs, stored by its endpoints.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Interval {
+    start: u32,
+    end: u32,
+}
+
+// Intervals are never empty, so an `is_empty` would always be false.
+#[allow(clippy::len_without_is_empty)]
+impl Interval {
+    /// The interval `start..=end`, or `None` if it would be empty.
+    pub fn new(start: u32, end: u32) -> Option<Self> {
+        (start <= end).then_some(Self { start, end })
+    }
+
+    /// The first ID in the interval.
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+
+    /// The last ID in the interval.
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+
+    /// The number of IDs in the interval.
+    pub fn len(&self) -> u64 {
+        u64::from(self.end - self.start) + 1
+    }
+
+    /// Whether `id` is in the interval.
+    pub fn contains(&self, id: u32) -> bool {
+        (self.start..=self.end).contains(&id)
+    }
+
+    /// Whether every ID in `other` is also in `self`.
+    pub fn covers(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Whether the intervals share at least one ID.
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// The IDs in both intervals, or `None` if they do not overlap.
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+}
+
+/// A union of intervals, kept as sorted, disjoint, non-adjacent intervals.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    /// The empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every ID in `interval`, merging it with any intervals it overlaps
+    /// or touches.
+    pub fn insert(&mut self, interval: Interval) {
+        let Interval { mut start, mut end } = interval;
+        let first = self
+            .intervals
+            .partition_point(|i| i.end.saturating_add(1) < start);
+        let last = self
+            .intervals
+            .partition_point(|i| i.start <= end.saturating_add(1));
+        if first < last {
+            start = start.min(self.intervals[first].start);
+            end = end.max(self.intervals[last - 1].end);
+        }
+        self.intervals
+            .splice(first..last, [Interval { start, end }]);
+    }
+
+    /// Every ID in either set.
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut union = self.clone();
+        union.extend(other.iter());
+        union
+    }
+
+    /// Whether `id` is in one of the set's intervals.
+    pub fn contains(&self, id: u32) -> bool {
+        let i = self.intervals.partition_point(|i| i.end < id);
+        self.intervals.get(i).is_some_and(|i| i.contains(id))
+    }
+
+    /// The number of IDs in the set.
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    /// Whether the set holds no IDs.
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The set's intervals in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Interval> + '_ {
+        self.intervals.iter().copied()
+    }
+}
+
+impl Extend<Interval> for IntervalSet {
+    fn extend<I: IntoIterator<Item = Interval>>(&mut self, iter: I) {
+        for interval in iter {
+            self.insert(interval);
+        }
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(start: u32, end: u32) -> Interval {
+        Interval::new(start, end).unwrap()
+    }
+
+    #[test]
+    fn test_interval() {
+        let a = interval(2, 8);
+        let b = interval(3, 7);
+        let c = interval(6, 10);
+        assert_eq!(Interval::new(5, 4), None);
+        assert_eq!(a.len(), 7);
+        assert_eq!(interval(0, u32::MAX).len(), 1 << 32);
+        assert!(a.covers(&b) && !b.covers(&a) && a.covers(&a));
+        assert!(a.overlaps(&c) && !interval(1, 2).overlaps(&interval(3, 4)));
+        assert_eq!(a.intersection(&c), Some(interval(6, 8)));
+        assert_eq!(interval(1, 2).intersection(&interval(3, 4)), None);
+        assert!(a.contains(2) && a.contains(8) && !a.contains(9));
+    }
+
+    #[test]
+    fn test_interval_set() {
+        let mut set = [interval(10, 12), interval(1, 3), interval(20, 20)]
+            .into_iter()
+            .collect::<IntervalSet>();
+        assert_eq!(set.len(), 7);
+        set.insert(interval(4, 9));
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [interval(1, 12), interval(20, 20)]
+        );
+        assert!(set.contains(12) && !set.contains(13) && !set.contains(0));
+
+        let other = [interval(15, 25), interval(u32::MAX, u32::MAX)]
+            .into_iter()
+            .collect::<IntervalSet>();
+        let union = set.union(&other);
+        assert_eq!(
+            union.iter().collect::<Vec<_>>(),
+            [
+                interval(1, 12),
+                interval(15, 25),
+                interval(u32::MAX, u32::MAX)
+            ]
+        );
+        assert_eq!(union.len(), 12 + 11 + 1);
+        assert!(IntervalSet::new().is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+//! The ledger of known answers that `solve --verify` and `--record` use.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use eyre::{bail, ensure, eyre, Context};
+
+use crate::runner::Answer;
+
+/// The known-good answers for each day and part, stored one per line as
+/// `dayN.partM = 1234` or `dayN.partM = "TEXT"`, with `\"` and `\\` escaping
+/// quotes and backslashes in text. Blank lines and lines starting with `#`
+/// are ignored, but kept when the ledger is written back out.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Ledger {
+    answers: BTreeMap<(u8, u8), Answer>,
+    lines: Vec<Line>,
+}
+
+/// One line of the ledger file, in file order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Line {
+    /// A blank or comment line, kept verbatim.
+    Text(String),
+    Answer(u8, u8),
+}
+
+/// How a computed answer compares to the ledger.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    /// The ledger holds the same answer.
+    Correct,
+    /// The ledger holds a different answer.
+    Incorrect {
+        /// The recorded answer.
+        expected: Answer,
+    },
+    /// The ledger has no answer for the day and part.
+    Unrecorded,
+}
+
+impl Ledger {
+    /// The recorded answer for `day` and `part`.
+    pub fn get(&self, day: u8, part: u8) -> Option<&Answer> {
+        self.answers.get(&(day, part))
+    }
+
+    /// Records an answer. A new entry goes after the last entry for an
+    /// earlier day and part, so a sorted ledger stays sorted.
+    pub fn insert(&mut self, day: u8, part: u8, answer: Answer) -> Option<Answer> {
+        let previous = self.answers.insert((day, part), answer);
+        if previous.is_none() {
+            let entries = self
+                .lines
+                .iter()
+                .enumerate()
+                .filter_map(|(i, line)| match *line {
+                    Line::Answer(d, p) => Some((i, (d, p))),
+                    Line::Text(_) => None,
+                })
+                .collect::<Vec<_>>();
+            let position = match entries.iter().rev().find(|&&(_, key)| key < (day, part)) {
+                Some(&(i, _)) => i + 1,
+                None => entries.first().map_or(self.lines.len(), |&(i, _)| i),
+            };
+            self.lines.insert(position, Line::Answer(day, part));
+        }
+
+        previous
+    }
+
+    /// Compares `answer` with the one recorded for `day` and `part`.
+    pub fn check(&self, day: u8, part: u8, answer: &Answer) -> Verdict {
+        match self.get(day, part) {
+            Some(expected) if expected == answer => Verdict::Correct,
+            Some(expected) => Verdict::Incorrect {
+                expected: expected.clone(),
+            },
+            None => Verdict::Unrecorded,
+        }
+    }
+}
+
+fn parse_key(key: &str) -> eyre::Result<(u8, u8)> {
+    let (day, part) = key
+        .strip_prefix("day")
+        .and_then(|key| key.split_once(".part"))
+        .ok_or_else(|| eyre!("expected a key like `day1.part2`, found {:?}", key))?;
+    Ok((day.parse()?, part.parse()?))
+}
+
+fn parse_answer(value: &str) -> eyre::Result<Answer> {
+    if let Some(quoted) = value.strip_prefix('"') {
+        let mut text = String::new();
+        let mut chars = quoted.chars();
+        loop {
+            match chars.next() {
+                None => bail!("unterminated string {:?}", value),
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some(c @ ('"' | '\\')) => text.push(c),
+                    _ => bail!("expected `\\\"` or `\\\\` in {:?}", value),
+                },
+                Some(c) => text.push(c),
+            }
+        }
+        ensure!(
+            chars.as_str().is_empty(),
+            "unexpected text after string {:?}",
+            value
+        );
+
+        return Ok(Answer::Text(text));
+    }
+
+    let n = value
+        .parse()
+        .with_context(|| format!("expected a number or quoted string, found {:?}", value))?;
+    Ok(Answer::Number(n))
+}
+
+impl FromStr for Ledger {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ledger = Ledger::default();
+        for (i, raw) in s.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                ledger.lines.push(Line::Text(raw.to_string()));
+                continue;
+            }
+
+            let (day, part, answer) = line
+                .split_once('=')
+                .ok_or_else(|| eyre!("expected `dayN.partM = answer`"))
+                .and_then(|(key, value)| {
+                    let (day, part) = parse_key(key.trim())?;
+                    Ok((day, part, parse_answer(value.trim())?))
+                })
+                .with_context(|| format!("ledger line {}", i + 1))?;
+
+            if ledger.answers.insert((day, part), answer).is_some() {
+                bail!("ledger line {}: duplicate day{}.part{}", i + 1, day, part);
+            }
+            ledger.lines.push(Line::Answer(day, part));
+        }
+
+        Ok(ledger)
+    }
+}
+
+impl fmt::Display for Ledger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            let (day, part) = match line {
+                Line::Text(text) => {
+                    writeln!(f, "{}", text)?;
+                    continue;
+                }
+                Line::Answer(day, part) => (day, part),
+            };
+
+            match &self.answers[&(*day, *part)] {
+                Answer::Number(n) => writeln!(f, "day{}.part{} = {}", day, part, n)?,
+                Answer::Text(s) => {
+                    let escaped = s.replace('\\', "\\\\").replace('"', "\\\"");
+                    writeln!(f, "day{}.part{} = \"{}\"", day, part, escaped)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let input = "day1.part1 = 24000\nday5.part1 = \"CMZ\"\nday10.part2 = \"\"\n";
+        let ledger = input.parse::<Ledger>().unwrap();
+        assert_eq!(ledger.get(1, 1), Some(&Answer::Number(24000)));
+        assert_eq!(ledger.get(5, 1), Some(&Answer::Text("CMZ".to_string())));
+        assert_eq!(ledger.get(1, 2), None);
+        assert_eq!(ledger.to_string(), input);
+    }
+
+    #[test]
+    fn test_comments() {
+        let input = "# answers\n\nday2.part1 = 15\n# day 5 is text\nday5.part1 = \"CMZ\"\n";
+        let mut ledger = input.parse::<Ledger>().unwrap();
+        assert_eq!(ledger.get(2, 1), Some(&Answer::Number(15)));
+        assert_eq!(ledger.to_string(), input);
+
+        ledger.insert(1, 1, Answer::Number(1));
+        ledger.insert(2, 2, Answer::Number(22));
+        ledger.insert(9, 1, Answer::Number(9));
+        ledger.insert(2, 1, Answer::Number(16));
+        assert_eq!(
+            ledger.to_string(),
+            "# answers\n\nday1.part1 = 1\nday2.part1 = 16\nday2.part2 = 22\n\
+             # day 5 is text\nday5.part1 = \"CMZ\"\nday9.part1 = 9\n"
+        );
+    }
+
+    #[test]
+    fn test_escapes() {
+        let mut ledger = Ledger::default();
+        ledger.insert(10, 2, Answer::Text("a \"b\" \\c".to_string()));
+        let written = ledger.to_string();
+        assert_eq!(written, "day10.part2 = \"a \\\"b\\\" \\\\c\"\n");
+        assert_eq!(written.parse::<Ledger>().unwrap(), ledger);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!("day1.part1 = CMZ".parse::<Ledger>().is_err());
+        assert!("day1 = 3".parse::<Ledger>().is_err());
+        assert!("day1.part1 = \"CMZ".parse::<Ledger>().is_err());
+        assert!("day1.part1 = \"C\"MZ\"".parse::<Ledger>().is_err());
+        assert!("day1.part1 = \"C\\MZ\"".parse::<Ledger>().is_err());
+        assert!("day1.part1 = 1\nday1.part1 = 2".parse::<Ledger>().is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let mut ledger = Ledger::default();
+        ledger.insert(1, 1, Answer::Number(24000));
+        assert_eq!(ledger.check(1, 1, &Answer::Number(24000)), Verdict::Correct);
+        assert_eq!(
+            ledger.check(1, 1, &Answer::Number(1)),
+            Verdict::Incorrect {
+                expected: Answer::Number(24000)
+            }
+        );
+        assert_eq!(ledger.check(1, 2, &Answer::Number(1)), Verdict::Unrecorded);
+    }
+}
@@ -1,20 +1,52 @@
+//! Solutions to Advent of Code 2022.
+//!
+//! Each `dayN` module exposes a `generator` that parses the puzzle input and
+//! `part1`/`part2` functions that solve it, for example:
+//!
+//! ```
+//! use advent_2022::day1;
+//!
+//! let elves = day1::generator("1000\n2000\n\n4000").unwrap();
+//! assert_eq!(day1::part1(&elves), Some(4000));
+//! ```
+//!
+//! [`runner`] drives any day with its input type erased.
+
+#![warn(missing_docs)]
+
 use aoc_runner_derive::aoc_lib;
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+// Loads a worked example from `input/examples`, trimmed the same way
+// aoc-runner trims the real input.
+#[cfg(test)]
+macro_rules! example {
+    ($name:literal) => {
+        include_str!(concat!("../input/examples/", $name, ".txt")).trim_end_matches('\n')
+    };
+}
+
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod error;
+pub mod grid;
+pub mod inputs;
+pub mod interval;
+pub mod ledger;
+pub mod runner;
+pub mod tournament;
 
 aoc_lib! { year = 2022 }
@@ -0,0 +1,193 @@
+//! A table of every solved day, for driving the solutions by day number.
+
+use std::{any::Any, fmt};
+
+use eyre::eyre;
+
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day2, day3, day4, day5, day6, day7,
+    day8, day9, error::ParseError,
+};
+
+/// A solved puzzle answer, either a number or a string such as day 5's crate
+/// letters.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Answer {
+    /// A numeric answer.
+    Number(i64),
+    /// A textual answer, such as letters read off a screen.
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Converts whatever a part function returns into an [`Answer`].
+pub trait IntoAnswer {
+    /// The answer, or why there is none.
+    fn into_answer(self) -> eyre::Result<Answer>;
+}
+
+macro_rules! number_answer {
+    ($($t:ty),*) => {
+        $(
+            impl IntoAnswer for $t {
+                fn into_answer(self) -> eyre::Result<Answer> {
+                    let n = i64::try_from(self).map_err(|_| eyre!("answer {} is too large", self))?;
+                    Ok(Answer::Number(n))
+                }
+            }
+        )*
+    };
+}
+
+number_answer!(i32, i64, u32, u64, usize);
+
+impl IntoAnswer for String {
+    fn into_answer(self) -> eyre::Result<Answer> {
+        Ok(Answer::Text(self))
+    }
+}
+
+impl<T: IntoAnswer> IntoAnswer for Option<T> {
+    fn into_answer(self) -> eyre::Result<Answer> {
+        self.ok_or_else(|| eyre!("no answer found"))?.into_answer()
+    }
+}
+
+impl<T: IntoAnswer> IntoAnswer for eyre::Result<T> {
+    fn into_answer(self) -> eyre::Result<Answer> {
+        self?.into_answer()
+    }
+}
+
+type Generator = Box<dyn Fn(&str) -> Result<Box<dyn Any>, ParseError> + Send + Sync>;
+type Part = Box<dyn Fn(&dyn Any) -> eyre::Result<Answer> + Send + Sync>;
+
+/// One day's generator and parts, with the parsed input type erased so that
+/// every day can be driven the same way.
+pub struct Solution {
+    /// The day of December the puzzle is for.
+    pub day: u8,
+    generator: Generator,
+    parts: [Part; 2],
+}
+
+impl Solution {
+    fn new<T, G, P1, P2>(day: u8, generator: G, part1: P1, part2: P2) -> Self
+    where
+        T: Any,
+        G: Fn(&str) -> Result<T, ParseError> + Send + Sync + 'static,
+        P1: Fn(&T) -> eyre::Result<Answer> + Send + Sync + 'static,
+        P2: Fn(&T) -> eyre::Result<Answer> + Send + Sync + 'static,
+    {
+        Self {
+            day,
+            generator: Box::new(move |input| Ok(Box::new(generator(input)?))),
+            parts: [
+                Box::new(move |input| part1(downcast(day, input)?)),
+                Box::new(move |input| part2(downcast(day, input)?)),
+            ],
+        }
+    }
+
+    /// Parses raw puzzle input, trimming trailing newlines as aoc-runner does.
+    pub fn generate(&self, input: &str) -> Result<Box<dyn Any>, ParseError> {
+        (self.generator)(input.trim_end_matches('\n'))
+    }
+
+    /// Solves `part` (1 or 2) against input returned by [`Solution::generate`].
+    pub fn solve(&self, part: u8, input: &dyn Any) -> eyre::Result<Answer> {
+        match part {
+            1 | 2 => (self.parts[usize::from(part - 1)])(input),
+            _ => Err(eyre!("day {} has no part {}", self.day, part)),
+        }
+    }
+}
+
+fn downcast<T: Any>(day: u8, input: &dyn Any) -> eyre::Result<&T> {
+    input
+        .downcast_ref()
+        .ok_or_else(|| eyre!("input was not generated for day {}", day))
+}
+
+macro_rules! solution {
+    ($day:literal, $module:ident) => {
+        Solution::new(
+            $day,
+            $module::generator,
+            |input| $module::part1(input).into_answer(),
+            |input| $module::part2(input).into_answer(),
+        )
+    };
+}
+
+/// Every solved day, in order.
+pub fn solutions() -> Vec<Solution> {
+    vec![
+        solution!(1, day1),
+        solution!(2, day2),
+        solution!(3, day3),
+        solution!(4, day4),
+        solution!(5, day5),
+        Solution::new(
+            6,
+            |input| Ok(input.as_bytes().to_vec()),
+            |input| day6::part1(input).into_answer(),
+            |input| day6::part2(input).into_answer(),
+        ),
+        solution!(7, day7),
+        solution!(8, day8),
+        solution!(9, day9),
+        solution!(10, day10),
+        solution!(11, day11),
+        solution!(12, day12),
+        solution!(13, day13),
+        solution!(14, day14),
+        solution!(15, day15),
+        solution!(16, day16),
+    ]
+}
+
+/// Looks up a single day's solution.
+pub fn solution(day: u8) -> Option<Solution> {
+    solutions().into_iter().find(|s| s.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let solution = solution(1).unwrap();
+        let input = solution.generate(example!("day1")).unwrap();
+        assert_eq!(
+            solution.solve(1, input.as_ref()).unwrap(),
+            Answer::Number(24000)
+        );
+        assert!(solution.solve(3, input.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_solve_text() {
+        let solution = solution(5).unwrap();
+        let input = solution.generate(example!("day5")).unwrap();
+        assert_eq!(
+            solution.solve(2, input.as_ref()).unwrap(),
+            Answer::Text("MCD".to_string())
+        );
+    }
+
+    #[test]
+    fn test_solve_wrong_day() {
+        let input = solution(1).unwrap().generate(example!("day1")).unwrap();
+        assert!(solution(2).unwrap().solve(1, input.as_ref()).is_err());
+    }
+}
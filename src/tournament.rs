@@ -0,0 +1,304 @@
+//! Round-robin rock-paper-scissors tournaments between day 2 strategies.
+
+use std::collections::HashMap;
+
+use eyre::ensure;
+
+use crate::{
+    day2::{Column, Outcome, Play, Rules},
+    inputs::Rng,
+};
+
+/// A way of choosing plays. Strategies see both players' plays so far, so
+/// the same strategy can take part in any number of matches.
+pub trait Strategy {
+    /// The next play, given both players' plays so far in the match.
+    fn play(&self, yours: &[Play], theirs: &[Play], rng: &mut Rng) -> Play;
+}
+
+/// Plays uniformly at random.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Random;
+
+impl Strategy for Random {
+    fn play(&self, _: &[Play], _: &[Play], rng: &mut Rng) -> Play {
+        *rng.choose(&Play::ALL)
+    }
+}
+
+/// Repeats a fixed sequence of plays.
+#[derive(Clone, Debug)]
+pub struct Fixed {
+    plays: Vec<Play>,
+}
+
+impl Fixed {
+    /// A strategy cycling through `plays`, which must not be empty.
+    pub fn new(plays: Vec<Play>) -> eyre::Result<Self> {
+        ensure!(
+            !plays.is_empty(),
+            "a fixed strategy needs at least one play"
+        );
+        Ok(Self { plays })
+    }
+}
+
+impl Strategy for Fixed {
+    fn play(&self, yours: &[Play], _: &[Play], _: &mut Rng) -> Play {
+        self.plays[yours.len() % self.plays.len()]
+    }
+}
+
+/// Follows a strategy guide round by round, starting over when it runs out.
+/// With [`Column::Outcome`], each round's play is the one that reaches the
+/// outcome against the move the guide expects.
+#[derive(Clone, Debug)]
+pub struct Guide(Fixed);
+
+impl Guide {
+    /// Reads `guide`, whose second column is read as `column` says.
+    pub fn new(guide: &[(char, char)], column: Column) -> eyre::Result<Self> {
+        let rules = match column {
+            Column::Move => Rules::by_move(),
+            Column::Outcome => Rules::by_outcome(),
+        };
+        let plays = guide
+            .iter()
+            .map(|&(opponent, response)| {
+                let (you, _) = rules.play(opponent, response)?;
+                Ok(Play::ALL[you])
+            })
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self(Fixed::new(plays)?))
+    }
+}
+
+impl Strategy for Guide {
+    fn play(&self, yours: &[Play], theirs: &[Play], rng: &mut Rng) -> Play {
+        self.0.play(yours, theirs, rng)
+    }
+}
+
+/// The most common of `plays`, with ties broken at random.
+fn most_common(plays: impl Iterator<Item = Play>, rng: &mut Rng) -> Option<Play> {
+    let mut counts = HashMap::<Play, usize>::new();
+    for play in plays {
+        *counts.entry(play).or_default() += 1;
+    }
+
+    let max = counts.values().copied().max()?;
+    let candidates = Play::ALL
+        .into_iter()
+        .filter(|play| counts.get(play) == Some(&max))
+        .collect::<Vec<_>>();
+    Some(*rng.choose(&candidates))
+}
+
+/// Beats whatever the opponent has played most often.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrequencyCounter;
+
+impl Strategy for FrequencyCounter {
+    fn play(&self, yours: &[Play], theirs: &[Play], rng: &mut Rng) -> Play {
+        match most_common(theirs.iter().copied(), rng) {
+            Some(play) => play.find_win(),
+            None => Random.play(yours, theirs, rng),
+        }
+    }
+}
+
+/// Predicts the opponent's next play from what followed their last `order`
+/// plays earlier in the match, and beats it.
+#[derive(Clone, Copy, Debug)]
+pub struct PatternPredictor {
+    /// The number of the opponent's latest plays to match.
+    pub order: usize,
+}
+
+impl Strategy for PatternPredictor {
+    fn play(&self, yours: &[Play], theirs: &[Play], rng: &mut Rng) -> Play {
+        let Some(context) = theirs.len().checked_sub(self.order).map(|i| &theirs[i..]) else {
+            return Random.play(yours, theirs, rng);
+        };
+
+        let followers = theirs
+            .windows(self.order + 1)
+            .filter(|window| &window[..self.order] == context)
+            .map(|window| window[self.order]);
+        match most_common(followers, rng) {
+            Some(play) => play.find_win(),
+            None => Random.play(yours, theirs, rng),
+        }
+    }
+}
+
+/// A named strategy entered into a tournament.
+pub struct Entrant {
+    /// The name the entrant's standing is listed under.
+    pub name: String,
+    /// How the entrant plays.
+    pub strategy: Box<dyn Strategy>,
+}
+
+impl Entrant {
+    /// Enters `strategy` under `name`.
+    pub fn new(name: impl Into<String>, strategy: impl Strategy + 'static) -> Self {
+        Self {
+            name: name.into(),
+            strategy: Box::new(strategy),
+        }
+    }
+}
+
+/// One entrant's results across the tournament, scored as in day 2.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Standing {
+    /// The entrant's name.
+    pub name: String,
+    /// The total of the day 2 scores of every round played.
+    pub score: usize,
+    /// Rounds won.
+    pub wins: usize,
+    /// Rounds drawn.
+    pub draws: usize,
+    /// Rounds lost.
+    pub losses: usize,
+}
+
+impl Standing {
+    fn record(&mut self, play: Play, outcome: Outcome) {
+        self.score += play.score() + outcome.score();
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+    }
+}
+
+/// Every entrant plays every other for `rounds` rounds. The same seed always
+/// gives the same standings.
+#[derive(Clone, Copy, Debug)]
+pub struct Tournament {
+    /// The number of rounds in each match.
+    pub rounds: usize,
+    /// The seed for every random choice in the tournament.
+    pub seed: u64,
+}
+
+impl Tournament {
+    /// Plays every match and returns the standings, highest score first.
+    pub fn run(&self, entrants: &[Entrant]) -> Vec<Standing> {
+        let mut rng = Rng::new(self.seed);
+        let mut standings = entrants
+            .iter()
+            .map(|entrant| Standing {
+                name: entrant.name.clone(),
+                ..Standing::default()
+            })
+            .collect::<Vec<_>>();
+
+        for a in 0..entrants.len() {
+            for b in a + 1..entrants.len() {
+                let (mut plays_a, mut plays_b) = (vec![], vec![]);
+                for _ in 0..self.rounds {
+                    let play_a = entrants[a].strategy.play(&plays_a, &plays_b, &mut rng);
+                    let play_b = entrants[b].strategy.play(&plays_b, &plays_a, &mut rng);
+                    standings[a].record(play_a, play_a.play(play_b));
+                    standings[b].record(play_b, play_b.play(play_a));
+                    plays_a.push(play_a);
+                    plays_b.push(play_b);
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+        standings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entrants() -> Vec<Entrant> {
+        vec![
+            Entrant::new("rock", Fixed::new(vec![Play::Rock]).unwrap()),
+            Entrant::new(
+                "cycle",
+                Fixed::new(vec![Play::Rock, Play::Paper, Play::Scissors]).unwrap(),
+            ),
+            Entrant::new("random", Random),
+            Entrant::new("frequency", FrequencyCounter),
+            Entrant::new("pattern", PatternPredictor { order: 2 }),
+        ]
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let tournament = Tournament {
+            rounds: 100,
+            seed: 42,
+        };
+        assert_eq!(tournament.run(&entrants()), tournament.run(&entrants()));
+    }
+
+    #[test]
+    fn test_fixed() {
+        let tournament = Tournament {
+            rounds: 10,
+            seed: 0,
+        };
+        let entrants = [
+            Entrant::new("rock", Fixed::new(vec![Play::Rock]).unwrap()),
+            Entrant::new("paper", Fixed::new(vec![Play::Paper]).unwrap()),
+        ];
+        let standings = tournament.run(&entrants);
+        assert_eq!(standings[0].name, "paper");
+        assert_eq!((standings[0].wins, standings[0].score), (10, 10 * (2 + 6)));
+        assert_eq!((standings[1].losses, standings[1].score), (10, 10));
+        assert!(Fixed::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_adaptive_strategies() {
+        let tournament = Tournament {
+            rounds: 100,
+            seed: 7,
+        };
+        let entrants = [
+            Entrant::new("rock", Fixed::new(vec![Play::Rock]).unwrap()),
+            Entrant::new("frequency", FrequencyCounter),
+        ];
+        assert!(tournament.run(&entrants)[0].wins >= 99);
+
+        let entrants = [
+            Entrant::new(
+                "cycle",
+                Fixed::new(vec![Play::Rock, Play::Paper, Play::Scissors]).unwrap(),
+            ),
+            Entrant::new("pattern", PatternPredictor { order: 2 }),
+        ];
+        let standings = tournament.run(&entrants);
+        assert_eq!(standings[0].name, "pattern");
+        assert!(standings[0].wins >= 95);
+    }
+
+    #[test]
+    fn test_guide() {
+        let guide = [('A', 'Y'), ('B', 'X'), ('C', 'Z')];
+        let rng = &mut Rng::new(0);
+        let by_move = Guide::new(&guide, Column::Move).unwrap();
+        let by_outcome = Guide::new(&guide, Column::Outcome).unwrap();
+        let plays = [Play::Paper, Play::Rock, Play::Scissors];
+        let outcomes = [Play::Rock, Play::Rock, Play::Rock];
+        for i in 0..3 {
+            assert_eq!(by_move.play(&plays[..i], &[], rng), plays[i]);
+            assert_eq!(by_outcome.play(&outcomes[..i], &[], rng), outcomes[i]);
+        }
+
+        assert!(Guide::new(&[('A', 'A')], Column::Outcome).is_err());
+        assert!(Guide::new(&[('A', 'A')], Column::Move).is_err());
+        assert!(Guide::new(&[('X', 'Y')], Column::Move).is_err());
+    }
+}